@@ -0,0 +1,50 @@
+//! Right-to-left and mixed-direction text.
+
+use unicode_bidi::{Level, ParagraphBidiInfo};
+
+use {Direction, Settings, str_width};
+
+/// `line`, which is in logical order, in the order it is displayed in a paragraph of `level`.
+fn reorder(line: &str, level: Level) -> String {
+    let info = ParagraphBidiInfo::new(line, Some(level));
+    info.reorder_line(0..line.len()).into_owned()
+}
+
+/// Whether `text`, a paragraph, is right-to-left according to `settings.direction`.
+fn is_rtl(text: &str, settings: &Settings) -> bool {
+    match settings.direction {
+        Direction::Ltr => false,
+        Direction::Rtl => true,
+        // Rules P2 and P3 of UAX #9: the first strong character decides.
+        Direction::Auto => ParagraphBidiInfo::new(text, None).paragraph_level.is_rtl()
+    }
+}
+
+/// Lays out `justified`, the lines of the paragraph `text` as justified in logical order,
+/// according to `settings.direction` and `settings.visual_order`. The lines of a right-to-left
+/// paragraph that don't reach `settings.width` are padded on their left when displayed.
+pub(crate) fn lay_out(text: &str, justified: String, settings: &Settings) -> String {
+    let rtl = is_rtl(text, settings);
+    if !rtl && !settings.visual_order {
+        return justified
+    }
+    let level = if rtl { Level::rtl() } else { Level::ltr() };
+    let (body, end) = match justified.strip_suffix(settings.newline) {
+        Some(body) => (body, settings.newline),
+        None => (&justified[..], "")
+    };
+
+    let lines: Vec<String> = body.split(settings.newline)
+        .map(|line| {
+            let mut line = if settings.visual_order { reorder(line, level) } else { line.to_owned() };
+            if rtl {
+                // In logical order the end of the line is on the left.
+                let pad = " ".repeat(settings.width.saturating_sub(str_width(&line, settings)));
+                if settings.visual_order { line.insert_str(0, &pad) } else { line += &pad }
+            }
+            line
+        })
+        .collect();
+
+    lines.join(settings.newline) + end
+}
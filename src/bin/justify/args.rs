@@ -0,0 +1,722 @@
+//! Command line parsing.
+
+use std::fmt;
+use std::fs;
+use std::sync::Mutex;
+
+use justify::{BoxStyle, Columns, Dictionary, Direction, Frame, HyphenationExceptions, InsertAt, InsertAtBuf, Language, Normalization,
+    Pagination, SeededRandom, Settings, SingleWord};
+
+use terminal;
+
+/// How `Options::width` is given on the command line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Width {
+    /// A number of columns.
+    Fixed(usize),
+    /// The width of the terminal.
+    Auto,
+    /// The width of the terminal, minus this many columns.
+    Margin(usize)
+}
+
+impl Width {
+    /// The number of columns to justify to, output going to standard output if `to_stdout`.
+    /// Output that goes anywhere else, such as a file, is justified to
+    /// `terminal::DEFAULT_WIDTH` rather than to the terminal, so it doesn't depend on the window
+    /// it was made in.
+    pub fn columns(self, to_stdout: bool) -> usize {
+        let terminal = if to_stdout { terminal::width() } else { terminal::DEFAULT_WIDTH };
+        match self {
+            Width::Fixed(w) => w,
+            Width::Auto => terminal,
+            // Never justify to less than one column.
+            Width::Margin(m) => terminal.saturating_sub(m).max(1)
+        }
+    }
+}
+
+/// Everything that can be set from the command line. Unlike `Settings`, owns its strings.
+pub struct Options {
+    pub help: bool,
+    pub version: bool,
+    pub width: Width,
+    pub justify_last_line: bool,
+    pub hyphenate_overflow: bool,
+    pub insert_at: InsertAtBuf,
+    /// The distributor for `--insert-at=random`, used instead of `insert_at` if set. It carries
+    /// on from one paragraph to the next.
+    pub random: Option<Mutex<SeededRandom>>,
+    pub wcwidth: bool,
+    pub ambiguous_wide: bool,
+    pub direction: Direction,
+    pub visual_order: bool,
+    pub normalization: Normalization,
+    pub preserve_form: bool,
+    pub ignore_spaces: bool,
+    pub newline: String,
+    pub hyphen: String,
+    pub separator: String,
+    pub single_word: SingleWord,
+    pub min_last_line_words: usize,
+    pub min_last_line_width: usize,
+    pub balance_columns: bool,
+    pub kashida: bool,
+    pub dictionary: Option<Dictionary>,
+    pub language: Language,
+    pub left_hyphen_min: usize,
+    pub right_hyphen_min: usize,
+    pub max_hyphenated_lines: usize,
+    pub hyphenation_exceptions: Option<HyphenationExceptions>,
+    /// Lay the text out in this many columns side by side.
+    pub columns: usize,
+    /// Spaces between `columns`.
+    pub gutter: usize,
+    /// Split the output into pages of this many lines.
+    pub page_length: Option<usize>,
+    pub header: String,
+    pub footer: String,
+    pub title: String,
+    pub form_feed: bool,
+    pub widows: usize,
+    pub orphans: usize,
+    /// Draw a box of this style around the text.
+    pub boxed: Option<BoxStyle>,
+    pub padding: usize,
+    pub box_title: String,
+    /// Files to read; standard input if empty. `-` also stands for standard input.
+    pub files: Vec<String>,
+    /// Where to write output; standard output if `None`.
+    pub output: Option<String>,
+    /// Rewrite `files` in place, keeping backups with this suffix unless it is empty.
+    pub in_place: Option<String>,
+    /// Only report lines of `files` that aren't justified.
+    pub check: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let settings = Settings::default();
+        let pagination = Pagination::default();
+        let frame = Frame::default();
+        let columns = Columns::default();
+        Options {
+            help: false,
+            version: false,
+            width: Width::Auto,
+            justify_last_line: settings.justify_last_line,
+            hyphenate_overflow: settings.hyphenate_overflow,
+            insert_at: InsertAtBuf::Balanced,
+            random: None,
+            wcwidth: false,
+            ambiguous_wide: false,
+            direction: Direction::Ltr,
+            visual_order: false,
+            normalization: Normalization::None,
+            preserve_form: false,
+            ignore_spaces: settings.ignore_spaces,
+            newline: settings.newline.to_owned(),
+            hyphen: settings.hyphen.to_owned(),
+            separator: settings.separator.to_owned(),
+            single_word: settings.single_word,
+            min_last_line_words: settings.min_last_line_words,
+            min_last_line_width: settings.min_last_line_width,
+            balance_columns: columns.balance,
+            kashida: settings.kashida,
+            dictionary: None,
+            language: settings.language,
+            left_hyphen_min: settings.left_hyphen_min,
+            right_hyphen_min: settings.right_hyphen_min,
+            max_hyphenated_lines: settings.max_hyphenated_lines,
+            hyphenation_exceptions: None,
+            columns: 1,
+            gutter: columns.gutter,
+            page_length: None,
+            header: pagination.header.to_owned(),
+            footer: pagination.footer.to_owned(),
+            title: pagination.title.to_owned(),
+            form_feed: pagination.form_feed,
+            widows: pagination.widows,
+            orphans: pagination.orphans,
+            boxed: None,
+            padding: frame.padding,
+            box_title: frame.title.to_owned(),
+            files: Vec::new(),
+            output: None,
+            in_place: None,
+            check: false
+        }
+    }
+}
+
+impl Options {
+    /// Whether the output goes to standard output, rather than to files or nowhere.
+    fn to_stdout(&self) -> bool {
+        self.output.is_none() && self.in_place.is_none() && !self.check
+    }
+
+    /// The `Settings` these options describe.
+    pub fn settings(&self) -> Settings<'_> {
+        Settings {
+            justify_last_line: self.justify_last_line,
+            hyphenate_overflow: self.hyphenate_overflow,
+            width: self.width.columns(self.to_stdout()),
+            insert_at: match self.random {
+                Some(ref random) => InsertAt::Distributor(random),
+                None => self.insert_at.into()
+            },
+            #[cfg(feature="unicode-width")]
+            wcwidth: self.wcwidth,
+            ambiguous_wide: self.ambiguous_wide,
+            direction: self.direction,
+            visual_order: self.visual_order,
+            normalization: self.normalization,
+            preserve_form: self.preserve_form,
+            ignore_spaces: self.ignore_spaces,
+            newline: &self.newline,
+            hyphen: &self.hyphen,
+            separator: &self.separator,
+            single_word: self.single_word,
+            min_last_line_words: self.min_last_line_words,
+            min_last_line_width: self.min_last_line_width,
+            kashida: self.kashida,
+            dictionary: self.dictionary.as_ref(),
+            language: self.language,
+            left_hyphen_min: self.left_hyphen_min,
+            right_hyphen_min: self.right_hyphen_min,
+            max_hyphenated_lines: self.max_hyphenated_lines,
+            hyphenation_exceptions: self.hyphenation_exceptions.as_ref()
+        }
+    }
+
+    /// The `Pagination` these options describe, if the output is to be paginated.
+    pub fn pagination(&self) -> Option<Pagination<'_>> {
+        Some(Pagination {
+            length: self.page_length?,
+            header: &self.header,
+            footer: &self.footer,
+            title: &self.title,
+            form_feed: self.form_feed,
+            widows: self.widows,
+            orphans: self.orphans
+        })
+    }
+
+    /// The `Columns` these options describe.
+    pub fn columns(&self) -> Columns {
+        Columns {
+            count: self.columns,
+            gutter: self.gutter,
+            balance: self.balance_columns
+        }
+    }
+
+    /// The `Frame` these options describe, if the text is to be boxed.
+    pub fn frame(&self) -> Option<Frame<'_>> {
+        Some(Frame {
+            style: self.boxed?,
+            padding: self.padding,
+            title: &self.box_title
+        })
+    }
+
+    /// Sets the option called `name` (its long name, without dashes) to `value`. `value` is `None`
+    /// for flags being turned on; they may also be given as `true` or `false`.
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<(), Error> {
+        let on = || match value {
+            None | Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            Some(v) => Err(Error::Invalid(name.to_owned(), v.to_owned()))
+        };
+        let value = value.unwrap_or("");
+        match name {
+            "help" => self.help = true,
+            "version" => self.version = true,
+            "wcwidth" => self.wcwidth = on()?,
+            "ambiguous-wide" => self.ambiguous_wide = on()?,
+            "direction" => self.direction = direction(value)?,
+            "visual" => self.visual_order = on()?,
+            "normalize" => self.normalization = normalization(value)?,
+            "preserve-form" => self.preserve_form = on()?,
+            "justify-last-line" => self.justify_last_line = on()?,
+            "hyphenate" => self.hyphenate_overflow = on()?,
+            "ignore-spaces" => self.ignore_spaces = on()?,
+            "kashida" => self.kashida = on()?,
+            "dictionary" => self.dictionary = Some(dictionary(value)?),
+            "language" => self.language = language(value)?,
+            "left" => { self.insert_at = InsertAtBuf::Left; self.random = None },
+            "right" => { self.insert_at = InsertAtBuf::Right; self.random = None },
+            "width" => self.width = width(value)?,
+            "insert-at" => match random(value) {
+                Some(seed) => self.random = Some(Mutex::new(SeededRandom::new(seed?))),
+                None => { self.insert_at = insert_at(value)?; self.random = None }
+            },
+            "newline" => self.newline = unescape(value),
+            "hyphen" => self.hyphen = unescape(value),
+            "left-hyphen-min" => self.left_hyphen_min = number(name, value)?,
+            "right-hyphen-min" => self.right_hyphen_min = number(name, value)?,
+            "hyphen-limit" => self.max_hyphenated_lines = number(name, value)?,
+            "exceptions" => self.hyphenation_exceptions = Some(exceptions(value)?),
+            "separator" => self.separator = unescape(value),
+            "single-word" => self.single_word = single_word(value)?,
+            "min-last-words" => self.min_last_line_words = number(name, value)?,
+            "min-last-width" => self.min_last_line_width = number(name, value)?,
+            "columns" => self.columns = number(name, value)?.max(1),
+            "gutter" => self.gutter = number(name, value)?,
+            "balance" => self.balance_columns = on()?,
+            "page-length" => self.page_length = Some(number(name, value)?),
+            "header" => self.header = unescape(value),
+            "footer" => self.footer = unescape(value),
+            "title" => self.title = unescape(value),
+            "form-feed" => self.form_feed = on()?,
+            "widows" => self.widows = number(name, value)?,
+            "orphans" => self.orphans = number(name, value)?,
+            "box" => self.boxed = Some(box_style(value)?),
+            "padding" => self.padding = number(name, value)?,
+            "box-title" => self.box_title = unescape(value),
+            "output" => self.output = Some(value.to_owned()),
+            "in-place" => self.in_place = Some(value.to_owned()),
+            "check" => self.check = true,
+            "file" => self.files.push(value.to_owned()),
+            // Handled before the other options, see `main`.
+            "profile" | "no-config" => {},
+            _ => return Err(Error::Unknown(name.to_owned()))
+        }
+        Ok(())
+    }
+
+    /// Checks for options that can't be used together.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.check && (self.in_place.is_some() || self.output.is_some()) {
+            return Err(Error::Usage("--check can't be used with --in-place or --output".to_owned()));
+        }
+        if self.check && (self.columns > 1 || self.page_length.is_some() || self.boxed.is_some()) {
+            return Err(Error::Usage("--check can't be used with --columns, --page-length or --box".to_owned()));
+        }
+        if self.columns > 1 && self.boxed.is_some() {
+            return Err(Error::Usage("--columns and --box can't be used together".to_owned()));
+        }
+        if self.in_place.is_some() {
+            if self.output.is_some() {
+                return Err(Error::Usage("--in-place and --output can't be used together".to_owned()));
+            }
+            if self.files.is_empty() || self.files.iter().any(|f| f == "-") {
+                return Err(Error::Usage("--in-place needs files to rewrite".to_owned()));
+            }
+        }
+
+        self.settings().validate().map_err(|e| Error::Usage(e.to_string()))
+    }
+}
+
+/// A command line error. The program should exit with status 2 after printing it.
+#[derive(Debug)]
+pub enum Error {
+    /// An option that doesn't exist.
+    Unknown(String),
+    /// An option that needs a value was given none.
+    MissingValue(String),
+    /// An option that takes no value was given one.
+    UnexpectedValue(String),
+    /// The value of an option (first) could not be understood (second).
+    Invalid(String, String),
+    /// Options that can't be used together, or a missing argument.
+    Usage(String),
+    /// A problem with a configuration file or profile.
+    Config(String)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Unknown(ref o) => write!(f, "unknown option `{}`", o),
+            Error::MissingValue(ref o) => write!(f, "option `--{}` requires a value", o),
+            Error::UnexpectedValue(ref o) => write!(f, "option `--{}` does not take a value", o),
+            Error::Invalid(ref o, ref v) => write!(f, "invalid value `{}` for option `--{}`", v, o),
+            Error::Usage(ref s) | Error::Config(ref s) => write!(f, "{}", s)
+        }
+    }
+}
+
+/// Whether an option takes a value.
+#[derive(Clone, Copy, PartialEq)]
+enum Value {
+    No,
+    Required,
+    /// Only given as `--option=value`.
+    Optional
+}
+
+/// (short name, long name, whether the option takes a value)
+const OPTIONS: &[(Option<char>, &str, Value)] = &[
+    (Some('h'), "help", Value::No),
+    (Some('V'), "version", Value::No),
+    (Some('w'), "wcwidth", Value::No),
+    (Some('A'), "ambiguous-wide", Value::No),
+    (Some('j'), "justify-last-line", Value::No),
+    (Some('H'), "hyphenate", Value::No),
+    (Some('i'), "ignore-spaces", Value::No),
+    (None, "direction", Value::Required),
+    (None, "visual", Value::No),
+    (None, "normalize", Value::Required),
+    (None, "preserve-form", Value::No),
+    (Some('k'), "kashida", Value::No),
+    (Some('d'), "dictionary", Value::Required),
+    (Some('L'), "language", Value::Required),
+    (Some('l'), "left", Value::No),
+    (Some('r'), "right", Value::No),
+    (Some('W'), "width", Value::Required),
+    (None, "insert-at", Value::Required),
+    (None, "newline", Value::Required),
+    (None, "hyphen", Value::Required),
+    (None, "left-hyphen-min", Value::Required),
+    (None, "right-hyphen-min", Value::Required),
+    (None, "hyphen-limit", Value::Required),
+    (Some('e'), "exceptions", Value::Required),
+    (None, "separator", Value::Required),
+    (None, "single-word", Value::Required),
+    (None, "min-last-words", Value::Required),
+    (None, "min-last-width", Value::Required),
+    (Some('C'), "columns", Value::Required),
+    (None, "gutter", Value::Required),
+    (Some('b'), "balance", Value::No),
+    (Some('P'), "page-length", Value::Required),
+    (None, "header", Value::Required),
+    (None, "footer", Value::Required),
+    (Some('t'), "title", Value::Required),
+    (Some('f'), "form-feed", Value::No),
+    (None, "widows", Value::Required),
+    (None, "orphans", Value::Required),
+    (Some('B'), "box", Value::Optional),
+    (None, "padding", Value::Required),
+    (None, "box-title", Value::Required),
+    (Some('o'), "output", Value::Required),
+    (None, "in-place", Value::Optional),
+    (Some('c'), "check", Value::No),
+    (Some('p'), "profile", Value::Required),
+    (None, "no-config", Value::No)
+];
+
+/// Whether there is an option called `name` (its long name, without dashes).
+pub fn is_option(name: &str) -> bool {
+    OPTIONS.iter().any(|o| o.1 == name)
+}
+
+pub const USAGE: &str = "Usage: justify [OPTION]... [WIDTH] [FILE]...
+Justify the text in each FILE, or standard input if there are none or FILE is
+-, and write it to standard output.
+
+  -W, --width=N            Justify to N columns. N may also be `auto` (the
+                           default), the width of the terminal, or -M, the
+                           width of the terminal minus M columns. The
+                           terminal width is taken from $COLUMNS if standard
+                           output isn't a terminal, or else is 80; with -o,
+                           --in-place or --check it is always 80. A bare
+                           number is also taken as the width. Earlier
+                           versions defaulted to 80; scripts should give a
+                           width so that their output doesn't depend on the
+                           terminal.
+  -j, --justify-last-line  Justify the last line of each paragraph as well.
+  -H, --hyphenate          Hyphenate words that are longer than the width.
+  -i, --ignore-spaces      Ignore spaces when justifying; use with -H.
+  -w, --wcwidth            Take Unicode widths into account (needs the
+                           `unicode-width` feature, otherwise ignored).
+  -A, --ambiguous-wide     With -w, count characters of ambiguous width (such
+                           as ·, — and box drawing) as two columns wide, as
+                           terminals in CJK locales show them.
+      --direction=DIR      The direction paragraphs are written in: ltr (the
+                           default), rtl or auto, from the first letter of
+                           each. Right-to-left paragraphs are flush right.
+                           Needs the `bidi` feature, otherwise ignored.
+      --visual             Write lines in the order they are displayed in,
+                           for terminals without bidirectional text support.
+                           Needs the `bidi` feature, otherwise ignored.
+      --normalize=FORM     Normalize the text to FORM before measuring it:
+                           none (the default), nfc or nfkc. Needs the
+                           `normalization` feature, otherwise ignored.
+      --preserve-form      Keep the text in its original form in the output,
+                           only measuring it normalized.
+  -k, --kashida            Justify Arabic by elongating words with kashidas
+                           before widening spaces.
+  -d, --dictionary=FILE    Break lines between the words of Thai, Lao, Khmer
+                           and Burmese text, as found in FILE, a list of
+                           words, one per line. FILE may also be :thai, a
+                           built-in list of Thai words (needs the
+                           `thai-dictionary` feature).
+  -L, --language=LANG      Follow the typographic rules of LANG, a language
+                           code or locale such as fr or cs_CZ.UTF-8: French
+                           (fr) spacing around punctuation, no single-letter
+                           words at the end of lines in Czech (cs), Polish
+                           (pl) and Slovak (sk), and `ck` in German (de) and
+                           `ij` in Dutch (nl) kept together.
+  -l, --left               Same as --insert-at=left.
+  -r, --right              Same as --insert-at=right.
+      --insert-at=WHERE    Where to put extra spaces: left, right, balanced
+                           (default), alternating, avoid-rivers,
+                           random[:SEED], which carries on from one
+                           paragraph to the next, or stateless-random[:SEED],
+                           which always lays out the same paragraph the same.
+      --single-word=HOW    How to pad lines of a single word: unpadded
+                           (default), letter-spacing or fill:CHAR.
+      --min-last-words=N   Avoid last lines of fewer than N words.
+      --min-last-width=N   Avoid last lines narrower than N columns.
+  -C, --columns=N          Lay the text out in N columns side by side, which
+                           together take up the width.
+      --gutter=N           Put N spaces between columns (default 2).
+  -b, --balance            Make the columns about the same height, instead of
+                           filling all but the last.
+  -P, --page-length=N      Split the output into pages of N lines.
+      --header=TEMPLATE    Print TEMPLATE, centered, at the top of each page.
+                           {title} stands for the title, {page} for the page
+                           number and {pages} for the number of pages.
+      --footer=TEMPLATE    Like --header, but at the bottom of each page.
+  -t, --title=STR          The title for --header and --footer.
+  -f, --form-feed          Separate pages with form feeds rather than padding
+                           them with blank lines.
+      --widows=N           Carry at least N lines of a paragraph over to the
+                           next page (default 2).
+      --orphans=N          Leave at least N lines of a paragraph at the bottom
+                           of a page (default 2).
+  -B, --box[=STYLE]        Draw a box around the text, within the width. STYLE
+                           is ascii, light (the default), heavy, double or
+                           rounded.
+      --padding=N          Put N spaces between the box and the text (default
+                           1).
+      --box-title=STR      Show STR in the top of the box.
+      --newline=STR        Line separator (default \\n).
+      --hyphen=STR         Hyphen used by -H (default -).
+      --left-hyphen-min=N  Leave at least N characters before a hyphen
+                           (default 1).
+      --right-hyphen-min=N Carry at least N characters of a hyphenated word
+                           over to the next line (default 1).
+      --hyphen-limit=N     End at most N lines in a row with a hyphen, leaving
+                           the rest of the word whole (default 0, no limit).
+  -e, --exceptions=FILE    Hyphenate the words in FILE, one per line, only
+                           where they have hyphens, e.g. ta-ble, and words
+                           without any not at all.
+      --separator=STR      Paragraph separator (default \\n\\n).
+  -o, --output=FILE        Write to FILE instead of standard output.
+      --in-place[=SUFFIX]  Rewrite each FILE with its justified contents. If
+                           SUFFIX is given, the original is kept as FILE
+                           followed by SUFFIX.
+  -c, --check              Don't write the justified text; instead list the
+                           lines of each FILE that aren't justified, and exit
+                           with status 1 if there are any.
+  -p, --profile=NAME       Use the options of profile NAME from the
+                           configuration files.
+      --no-config          Don't read configuration files.
+  -h, --help               Show this help and exit.
+  -V, --version            Show the version and exit.
+
+Short flags may be combined, e.g. `-Hl`. STR values understand the escapes
+\\n, \\r, \\t and \\\\. Arguments after `--` are always taken as files.
+
+Defaults for the options are read from $XDG_CONFIG_HOME/justify/config.toml
+(or ~/.config/justify/config.toml), then from the nearest .justify.toml in
+the current directory or above it, with later files taking precedence and
+options on the command line taking precedence over both. Keys are the long
+option names, except those of -l, -r, -o, -c, -p, --in-place, --no-config, -h
+and -V, files are found relative to the configuration file, and
+[profile.NAME] tables hold named profiles:
+
+    width = 72
+
+    [profile.cjk]
+    wcwidth = true
+    ignore-spaces = true
+
+The profiles cjk, email and commit are built in and may be overridden.";
+
+/// Parses the arguments (without the program name) into the options they set and their values,
+/// in order, for `Options::set`. Bare numbers set `width`; other arguments are given as `file`.
+pub fn parse<I: IntoIterator<Item=String>>(args: I) -> Result<Vec<(&'static str, Option<String>)>, Error> {
+    let mut args = args.into_iter();
+    let mut only_positional = false;
+    let mut ret = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if only_positional {
+            ret.push(("file", Some(arg)));
+        } else if arg == "-" || !arg.starts_with('-') {
+            // A bare number is the width, for compatibility with earlier versions.
+            let name = if arg.parse::<usize>().is_ok() { "width" } else { "file" };
+            ret.push((name, Some(arg)));
+        } else if arg == "--" {
+            only_positional = true;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.find('=') {
+                Some(i) => (&long[..i], Some(long[i+1..].to_owned())),
+                None => (long, None)
+            };
+            let &(_, name, takes_value) = OPTIONS.iter()
+                .find(|o| o.1 == name)
+                .ok_or_else(|| Error::Unknown(arg.clone()))?;
+            let value = match (takes_value, value) {
+                (Value::No, Some(_)) => return Err(Error::UnexpectedValue(name.to_owned())),
+                (Value::Required, None) => Some(args.next().ok_or_else(|| Error::MissingValue(name.to_owned()))?),
+                (_, value) => value
+            };
+            ret.push((name, value));
+        } else {
+            let shorts = &arg[1..];
+            for (i, c) in shorts.char_indices() {
+                let &(_, name, takes_value) = OPTIONS.iter()
+                    .find(|o| o.0 == Some(c))
+                    .ok_or_else(|| Error::Unknown(format!("-{}", c)))?;
+                if takes_value == Value::Required {
+                    // The rest of the argument is the value, e.g. `-W72`, else the next argument.
+                    let rest = &shorts[i+c.len_utf8()..];
+                    let value = if !rest.is_empty() {
+                        rest.to_owned()
+                    } else {
+                        args.next().ok_or_else(|| Error::MissingValue(name.to_owned()))?
+                    };
+                    ret.push((name, Some(value)));
+                    break
+                }
+                ret.push((name, None));
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
+fn width(value: &str) -> Result<Width, Error> {
+    if value == "auto" {
+        return Ok(Width::Auto)
+    }
+    let invalid = || Error::Invalid("width".to_owned(), value.to_owned());
+    match value.strip_prefix('-') {
+        Some(margin) => Ok(Width::Margin(margin.parse().map_err(|_| invalid())?)),
+        None => Ok(Width::Fixed(value.parse().map_err(|_| invalid())?))
+    }
+}
+
+fn number(name: &str, value: &str) -> Result<usize, Error> {
+    value.parse().map_err(|_| Error::Invalid(name.to_owned(), value.to_owned()))
+}
+
+fn insert_at(value: &str) -> Result<InsertAtBuf, Error> {
+    Ok(match value {
+        "left" => InsertAtBuf::Left,
+        "right" => InsertAtBuf::Right,
+        "balanced" => InsertAtBuf::Balanced,
+        "alternating" => InsertAtBuf::Alternating,
+        "avoid-rivers" => InsertAtBuf::AvoidRivers,
+        "stateless-random" => InsertAtBuf::StatelessRandom(0),
+        _ => match value.strip_prefix("stateless-random:").map(str::parse) {
+            Some(Ok(seed)) => InsertAtBuf::StatelessRandom(seed),
+            _ => return Err(Error::Invalid("insert-at".to_owned(), value.to_owned()))
+        }
+    })
+}
+
+/// The seed of `--insert-at=random[:SEED]`, if that is what `value` is.
+fn random(value: &str) -> Option<Result<u64, Error>> {
+    let seed = match value.strip_prefix("random") {
+        Some("") => return Some(Ok(0)),
+        Some(seed) => seed.strip_prefix(':')?,
+        None => return None
+    };
+    Some(seed.parse().map_err(|_| Error::Invalid("insert-at".to_owned(), value.to_owned())))
+}
+
+fn direction(value: &str) -> Result<Direction, Error> {
+    Ok(match value {
+        "ltr" => Direction::Ltr,
+        "rtl" => Direction::Rtl,
+        "auto" => Direction::Auto,
+        _ => return Err(Error::Invalid("direction".to_owned(), value.to_owned()))
+    })
+}
+
+fn normalization(value: &str) -> Result<Normalization, Error> {
+    Ok(match value {
+        "none" => Normalization::None,
+        "nfc" => Normalization::Nfc,
+        "nfkc" => Normalization::Nfkc,
+        _ => return Err(Error::Invalid("normalize".to_owned(), value.to_owned()))
+    })
+}
+
+fn language(value: &str) -> Result<Language, Error> {
+    // Only the language of a locale such as `fr_CA.UTF-8` matters.
+    let code = value.split(['_', '-', '.']).next().unwrap_or(value);
+    Ok(match code {
+        "none" | "C" | "POSIX" => Language::None,
+        "fr" => Language::French,
+        "cs" => Language::Czech,
+        "pl" => Language::Polish,
+        "sk" => Language::Slovak,
+        "de" => Language::German,
+        "nl" => Language::Dutch,
+        // Languages without rules of their own.
+        _ if code.len() == 2 && code.chars().all(|c| c.is_ascii_lowercase()) => Language::None,
+        _ => return Err(Error::Invalid("language".to_owned(), value.to_owned()))
+    })
+}
+
+fn dictionary(value: &str) -> Result<Dictionary, Error> {
+    #[cfg(feature="thai-dictionary")]
+    if value == ":thai" {
+        return Ok(Dictionary::thai())
+    }
+    fs::read_to_string(value)
+        .map(|list| Dictionary::from_list(&list))
+        .map_err(|e| Error::Usage(format!("can't read dictionary `{}`: {}", value, e)))
+}
+
+fn exceptions(value: &str) -> Result<HyphenationExceptions, Error> {
+    fs::read_to_string(value)
+        .map(|list| HyphenationExceptions::from_list(&list))
+        .map_err(|e| Error::Usage(format!("can't read exceptions `{}`: {}", value, e)))
+}
+
+fn box_style(value: &str) -> Result<BoxStyle, Error> {
+    Ok(match value {
+        "ascii" => BoxStyle::Ascii,
+        "" | "light" => BoxStyle::Light,
+        "heavy" => BoxStyle::Heavy,
+        "double" => BoxStyle::Double,
+        "rounded" => BoxStyle::Rounded,
+        _ => return Err(Error::Invalid("box".to_owned(), value.to_owned()))
+    })
+}
+
+fn single_word(value: &str) -> Result<SingleWord, Error> {
+    Ok(match value {
+        "unpadded" => SingleWord::Unpadded,
+        "letter-spacing" => SingleWord::LetterSpacing,
+        _ => {
+            let mut fill = value.strip_prefix("fill:").unwrap_or("").chars();
+            match (fill.next(), fill.next()) {
+                (Some(c), None) => SingleWord::Fill(c),
+                _ => return Err(Error::Invalid("single-word".to_owned(), value.to_owned()))
+            }
+        }
+    })
+}
+
+/// Replaces the escapes `\n`, `\r`, `\t` and `\\` in `s`; other backslashes are kept as is.
+fn unescape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            ret.push(c);
+            continue
+        }
+        match chars.next() {
+            Some('n') => ret.push('\n'),
+            Some('r') => ret.push('\r'),
+            Some('t') => ret.push('\t'),
+            Some('\\') => ret.push('\\'),
+            Some(c) => { ret.push('\\'); ret.push(c); },
+            None => ret.push('\\')
+        }
+    }
+
+    ret
+}
@@ -0,0 +1,146 @@
+//! Configuration files, in TOML: options outside any table, and `[profile.NAME]` tables.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toml::{Table, Value};
+
+use args::{self, Error, Options};
+
+/// Profiles that are always available. Configuration files can override them.
+const BUILTIN: &str = r#"
+[profile.cjk]
+wcwidth = true
+ignore-spaces = true
+hyphenate = true
+hyphen = ""
+
+[profile.email]
+width = 72
+
+[profile.commit]
+width = 72
+"#;
+
+/// Options that only make sense once, on the command line; all others may be set in a
+/// configuration file. `left` and `right` are left to `insert-at`.
+const COMMAND_LINE_ONLY: &[&str] = &[
+    "help", "version", "left", "right", "output", "in-place", "check", "profile", "no-config"
+];
+
+/// Options whose values are files, which are found relative to the configuration file.
+const PATHS: &[&str] = &["dictionary", "exceptions"];
+
+/// The contents of one configuration file.
+#[derive(Default)]
+struct Config {
+    /// Options outside any table, used whatever the profile.
+    defaults: Vec<(String, String)>,
+    /// The options of each profile.
+    profiles: Vec<(String, Vec<(String, String)>)>
+}
+
+/// An option set in a configuration file, as it would be given on the command line. Paths are
+/// taken relative to `dir`.
+fn entry(key: String, value: Value, dir: &Path) -> Result<(String, String), String> {
+    if !args::is_option(&key) || COMMAND_LINE_ONLY.contains(&&*key) {
+        return Err(format!("unknown key `{}`", key));
+    }
+    let value = match value {
+        // Built in values such as `:thai` aren't files.
+        Value::String(s) if PATHS.contains(&&*key) && !s.starts_with(':') => dir.join(s).to_string_lossy().into_owned(),
+        Value::String(s) => s,
+        Value::Integer(i) => i.to_string(),
+        Value::Boolean(b) => b.to_string(),
+        _ => return Err(format!("invalid value for `{}`", key))
+    };
+    Ok((key, value))
+}
+
+/// Parses a configuration file in the directory `dir`.
+fn parse(text: &str, dir: &Path) -> Result<Config, String> {
+    let table: Table = text.parse().map_err(|e: toml::de::Error| e.to_string())?;
+    let mut config = Config::default();
+
+    for (key, value) in table {
+        match (key.as_str(), value) {
+            ("profile", Value::Table(profiles)) => for (name, profile) in profiles {
+                let options = match profile {
+                    Value::Table(options) => options,
+                    _ => return Err(format!("`profile.{}` is not a table", name))
+                };
+                let entries = options.into_iter().map(|(k, v)| entry(k, v, dir)).collect::<Result<_, _>>()?;
+                config.profiles.push((name, entries));
+            },
+            // Other tables are skipped.
+            (_, Value::Table(_)) => {},
+            (_, value) => config.defaults.push(entry(key, value, dir)?)
+        }
+    }
+
+    Ok(config)
+}
+
+/// The configuration files that exist, in increasing order of precedence: the user's, then the
+/// nearest `.justify.toml` in the current directory or above it.
+fn files() -> Vec<PathBuf> {
+    let mut ret = Vec::new();
+
+    let user = env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")));
+    if let Some(dir) = user {
+        ret.push(dir.join("justify").join("config.toml"));
+    }
+    if let Ok(dir) = env::current_dir() {
+        ret.extend(dir.ancestors().map(|d| d.join(".justify.toml")).find(|f| f.is_file()));
+    }
+
+    ret.retain(|f| f.is_file());
+    ret
+}
+
+fn apply(options: &mut Options, entries: &[(String, String)], origin: &str) -> Result<(), Error> {
+    for (key, value) in entries {
+        options.set(key, Some(value)).map_err(|e| Error::Config(format!("{}: {}", origin, e)))?;
+    }
+    Ok(())
+}
+
+/// Sets `options` from the built in profiles and, unless `builtin_only`, the configuration files:
+/// first all their defaults, then the options of `profile`, if given.
+pub fn load(options: &mut Options, profile: Option<&str>, builtin_only: bool) -> Result<(), Error> {
+    let builtin = parse(BUILTIN, Path::new("")).expect("invalid built in profiles");
+    let mut configs = vec![("built in profiles".to_owned(), builtin)];
+
+    if !builtin_only {
+        for file in files() {
+            let name = file.display().to_string();
+            let text = fs::read_to_string(&file).map_err(|e| Error::Config(format!("{}: {}", name, e)))?;
+            let dir = file.parent().unwrap_or(Path::new(""));
+            let config = parse(&text, dir).map_err(|e| Error::Config(format!("{}: {}", name, e)))?;
+            configs.push((name, config));
+        }
+    }
+
+    for (name, config) in &configs {
+        apply(options, &config.defaults, name)?;
+    }
+
+    if let Some(profile) = profile {
+        let mut found = false;
+        for (name, config) in &configs {
+            for (_, entries) in config.profiles.iter().filter(|p| p.0 == profile) {
+                apply(options, entries, name)?;
+                found = true;
+            }
+        }
+        if !found {
+            return Err(Error::Config(format!("unknown profile `{}`", profile)));
+        }
+    }
+
+    Ok(())
+}
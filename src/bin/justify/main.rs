@@ -0,0 +1,291 @@
+extern crate justify;
+extern crate toml;
+use justify::{check_justified, justify, justify_boxed, justify_columns, paginate, Columns, Frame, Pagination, Settings};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, stdin, stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::process::exit;
+
+mod args;
+mod config;
+mod terminal;
+
+const WCWIDTH_ENABLED: bool = cfg!(feature="unicode-width");
+
+/// Options from the configuration files, overridden by those on the command line.
+fn get_options() -> Result<args::Options, args::Error> {
+    let mut options = args::Options::default();
+    let given = args::parse(env::args().skip(1))?;
+
+    let profile = given.iter().rev().find(|o| o.0 == "profile").and_then(|o| o.1.as_deref());
+    let no_config = given.iter().any(|o| o.0 == "no-config");
+    config::load(&mut options, profile, no_config)?;
+
+    for (name, value) in &given {
+        options.set(name, value.as_deref())?;
+    }
+    options.validate()?;
+
+    Ok(options)
+}
+
+fn get_options_or_exit() -> args::Options {
+    get_options().unwrap_or_else(|e| {
+        eprintln!("justify: {}\nTry `justify --help` for more information.", e);
+        exit(2);
+    })
+}
+
+fn exit_if_help(options: &args::Options) {
+    if options.help {
+        println!("{}", args::USAGE);
+        if WCWIDTH_ENABLED {
+            println!("\nUnicode functionality via `wcwidth` is available.");
+        } else {
+            println!("\nUnicode functionality via `wcwidth` is not available.");
+        }
+        exit(0);
+    }
+    if options.version {
+        println!("justify {}", env!("CARGO_PKG_VERSION"));
+        exit(0);
+    }
+}
+
+/// An I/O error, and whether it happened on the input or the output side.
+enum Failure {
+    Read(io::Error),
+    Write(io::Error)
+}
+
+impl From<Failure> for io::Error {
+    fn from(f: Failure) -> io::Error {
+        match f { Failure::Read(e) | Failure::Write(e) => e }
+    }
+}
+
+/// Writes justified paragraphs, putting `settings.separator` between them. With more than one
+/// column, a box or pages, the paragraphs are instead kept until `finish`, as the whole text is
+/// needed to lay them out.
+struct Paragraphs<'a, 's: 'a, W: Write> {
+    output: W,
+    settings: &'a Settings<'s>,
+    written: bool,
+    columns: Columns,
+    pagination: Option<&'a Pagination<'a>>,
+    frame: Option<&'a Frame<'a>>,
+    pending: String
+}
+
+impl<'a, 's, W: Write> Paragraphs<'a, 's, W> {
+    fn new(output: W, settings: &'a Settings<'s>) -> Self {
+        Paragraphs { output, settings, written: false, columns: Columns { count: 1, ..Columns::default() }, pagination: None, frame: None, pending: String::new() }
+    }
+
+    /// Lays the paragraphs out in columns.
+    fn columns(mut self, columns: Columns) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// Splits the output into pages.
+    fn pages(mut self, pagination: Option<&'a Pagination<'a>>) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    /// Draws a box around the text.
+    fn frame(mut self, frame: Option<&'a Frame<'a>>) -> Self {
+        self.frame = frame;
+        self
+    }
+
+    /// Justifies `paragraph`, which must not contain newlines, and writes it out.
+    fn write(&mut self, paragraph: &str) -> io::Result<()> {
+        if self.columns.count > 1 || self.pagination.is_some() || self.frame.is_some() {
+            if !self.pending.is_empty() {
+                self.pending += self.settings.newline;
+            }
+            self.pending += paragraph;
+            return Ok(())
+        }
+        let justified = justify(paragraph, self.settings);
+        // With `justify_last_line`, the paragraph already ends in a newline.
+        let justified = justified.strip_suffix(self.settings.newline).unwrap_or(&justified);
+        if self.written {
+            self.output.write_all(self.settings.separator.as_bytes())?;
+        }
+        self.written = true;
+        self.output.write_all(justified.as_bytes())
+    }
+
+    /// Ends the last paragraph with a newline and returns the underlying writer.
+    fn finish(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            let mut justified = if let Some(frame) = self.frame {
+                justify_boxed(&self.pending, self.settings, frame)
+            } else if self.columns.count > 1 {
+                justify_columns(&self.pending, self.settings, &self.columns)
+            } else {
+                justify(&self.pending, self.settings)
+            };
+            if let Some(pagination) = self.pagination {
+                justified = paginate(&justified, self.settings, pagination);
+            }
+            self.output.write_all(justified.as_bytes())?;
+            self.written = true;
+        }
+        if self.written {
+            self.output.write_all(self.settings.newline.as_bytes())?;
+        }
+        Ok(self.output)
+    }
+}
+
+/// Justifies everything read from `input` into `output`. Paragraphs are separated by blank lines;
+/// the lines of a paragraph are joined with spaces and re-flowed together. Only one paragraph is
+/// kept in memory at a time.
+fn process<R: BufRead, W: Write>(mut input: R, output: &mut Paragraphs<'_, '_, W>) -> Result<(), Failure> {
+    let mut line = String::new();
+    let mut paragraph = String::new();
+    loop {
+        line.clear();
+        let eof = input.read_line(&mut line).map_err(Failure::Read)? == 0;
+        let text = line.trim();
+        if !text.is_empty() {
+            if !paragraph.is_empty() { paragraph.push(' '); }
+            paragraph.push_str(text);
+        } else if !paragraph.is_empty() {
+            output.write(&paragraph).map_err(Failure::Write)?;
+            paragraph.clear();
+        }
+        if eof { return Ok(()) }
+    }
+}
+
+/// Justifies the file at `path` into `output`; `-` is standard input.
+fn process_path<W: Write>(path: &str, output: &mut Paragraphs<'_, '_, W>) -> Result<(), Failure> {
+    if path == "-" {
+        process(stdin().lock(), output)
+    } else {
+        process(BufReader::new(File::open(path).map_err(Failure::Read)?), output)
+    }
+}
+
+/// Replaces the file at `path` with its justified contents. The new contents are written to a
+/// temporary file next to it, which is then renamed over the original, so the file is never left
+/// half-written. If `suffix` isn't empty, the original is first copied to `path` + `suffix`.
+fn process_in_place(path: &str, suffix: &str, settings: &Settings, options: &args::Options) -> io::Result<()> {
+    let original = Path::new(path);
+    let pagination = options.pagination();
+    let frame = options.frame();
+    let name = original.file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let temp = original.with_file_name(format!(".{}.justify-{}", name.to_string_lossy(), std::process::id()));
+
+    let result = (|| {
+        let mut output = Paragraphs::new(BufWriter::new(File::create(&temp)?), settings)
+            .columns(options.columns())
+            .pages(pagination.as_ref())
+            .frame(frame.as_ref());
+        process(BufReader::new(File::open(original)?), &mut output)?;
+        output.finish()?.into_inner()?.sync_all()?;
+        fs::set_permissions(&temp, fs::metadata(original)?.permissions())?;
+        if !suffix.is_empty() {
+            fs::copy(original, format!("{}{}", path, suffix))?;
+        }
+        fs::rename(&temp, original)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Reports the lines of the file at `path` that aren't justified on `output`, `rustfmt --check`
+/// style. Returns whether there were any.
+fn check_path<W: Write>(path: &str, output: &mut W, settings: &Settings) -> Result<bool, Failure> {
+    let mut text = String::new();
+    if path == "-" {
+        stdin().read_to_string(&mut text)
+    } else {
+        File::open(path).and_then(|mut f| f.read_to_string(&mut text))
+    }.map_err(Failure::Read)?;
+
+    let name = if path == "-" { "<stdin>" } else { path };
+    let mismatches = check_justified(&text, settings);
+    for m in &mismatches {
+        let show = |l: &Option<String>| l.as_ref().map_or("(no line)".to_owned(), |l| format!("`{}`", l));
+        writeln!(output, "{}:{}: expected {}, found {}", name, m.line, show(&m.expected), show(&m.found))
+            .map_err(Failure::Write)?;
+    }
+
+    Ok(!mismatches.is_empty())
+}
+
+fn run(options: &args::Options) -> Result<(), (String, io::Error)> {
+    let settings = options.settings();
+    let stdin_only = ["-".to_owned()];
+    let files = if options.files.is_empty() { &stdin_only[..] } else { &options.files[..] };
+
+    if options.check {
+        let mut output = stdout().lock();
+        let mut unjustified = false;
+        for file in files {
+            unjustified |= check_path(file, &mut output, &settings).map_err(|f| match f {
+                Failure::Read(e) => (file.clone(), e),
+                Failure::Write(e) => ("standard output".to_owned(), e)
+            })?;
+        }
+        if unjustified {
+            output.flush().map_err(|e| ("standard output".to_owned(), e))?;
+            exit(1);
+        }
+        return Ok(())
+    }
+
+    if let Some(ref suffix) = options.in_place {
+        for file in files {
+            process_in_place(file, suffix, &settings, options).map_err(|e| (file.clone(), e))?;
+        }
+        return Ok(())
+    }
+
+    let output: Box<dyn Write> = match options.output {
+        Some(ref path) => Box::new(BufWriter::new(File::create(path).map_err(|e| (path.clone(), e))?)),
+        None => Box::new(BufWriter::new(stdout().lock()))
+    };
+    let output_name = options.output.clone().unwrap_or_else(|| "standard output".to_owned());
+    let pagination = options.pagination();
+    let frame = options.frame();
+    let mut output = Paragraphs::new(output, &settings)
+        .columns(options.columns())
+        .pages(pagination.as_ref())
+        .frame(frame.as_ref());
+
+    for file in files {
+        process_path(file, &mut output).map_err(|f| match f {
+            Failure::Read(e) => (file.clone(), e),
+            Failure::Write(e) => (output_name.clone(), e)
+        })?;
+    }
+
+    output.finish().and_then(|mut o| o.flush()).map_err(|e| (output_name, e))
+}
+
+fn main() {
+    let options = get_options_or_exit();
+    exit_if_help(&options);
+
+    match run(&options) {
+        Ok(()) => {},
+        // Being cut short by e.g. `head` isn't an error.
+        Err((_, ref e)) if e.kind() == io::ErrorKind::BrokenPipe => {},
+        Err((name, e)) => {
+            eprintln!("justify: {}: {}", name, e);
+            exit(1);
+        }
+    }
+}
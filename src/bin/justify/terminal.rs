@@ -0,0 +1,55 @@
+//! Finding out how wide the terminal is.
+
+use std::env;
+
+/// The width used when it can't be found out any other way.
+pub const DEFAULT_WIDTH: usize = 80;
+
+#[cfg(any(target_os="linux", target_os="android", target_os="macos", target_os="ios",
+          target_os="freebsd", target_os="openbsd", target_os="netbsd", target_os="dragonfly"))]
+mod sys {
+    use std::os::raw::{c_int, c_ulong, c_ushort};
+
+    #[repr(C)]
+    struct Winsize {
+        ws_row: c_ushort,
+        ws_col: c_ushort,
+        ws_xpixel: c_ushort,
+        ws_ypixel: c_ushort
+    }
+
+    #[cfg(any(target_os="linux", target_os="android"))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+    #[cfg(not(any(target_os="linux", target_os="android")))]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    /// The number of columns of the terminal `fd` refers to, if it is one.
+    pub fn columns(fd: c_int) -> Option<usize> {
+        let mut size = Winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        // SAFETY: TIOCGWINSZ only writes a `struct winsize` to the pointer it is given.
+        let ret = unsafe { ioctl(fd, TIOCGWINSZ, &mut size as *mut Winsize) };
+        if ret == 0 && size.ws_col > 0 { Some(size.ws_col as usize) } else { None }
+    }
+}
+
+#[cfg(not(any(target_os="linux", target_os="android", target_os="macos", target_os="ios",
+              target_os="freebsd", target_os="openbsd", target_os="netbsd", target_os="dragonfly")))]
+mod sys {
+    pub fn columns(_fd: i32) -> Option<usize> {
+        None
+    }
+}
+
+/// The width of the terminal output is written to: asked of standard output if it is one, then
+/// taken from `$COLUMNS`, and otherwise `DEFAULT_WIDTH`. Other terminals the program may be
+/// attached to, such as the one standard error goes to, aren't asked, as they needn't be where
+/// the output ends up.
+pub fn width() -> usize {
+    sys::columns(1)
+        .or_else(|| env::var("COLUMNS").ok().and_then(|c| c.trim().parse().ok()).filter(|&c| c > 0))
+        .unwrap_or(DEFAULT_WIDTH)
+}
@@ -0,0 +1,114 @@
+//! Justifying text inside a box.
+
+use {Settings, justify};
+#[cfg(feature="unicode-width")] use str_width;
+
+/// The characters a box is drawn with (use with `Frame`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// `+`, `-` and `|`.
+    Ascii,
+    /// Default; `┌`, `─` and `│`.
+    Light,
+    /// `┏`, `━` and `┃`.
+    Heavy,
+    /// `╔`, `═` and `║`.
+    Double,
+    /// Like `Light`, but with rounded corners: `╭`.
+    Rounded
+}
+
+impl BoxStyle {
+    /// (top left, top right, bottom left, bottom right, horizontal, vertical)
+    fn chars(self) -> (char, char, char, char, char, char) {
+        match self {
+            BoxStyle::Ascii => ('+', '+', '+', '+', '-', '|'),
+            BoxStyle::Light => ('┌', '┐', '└', '┘', '─', '│'),
+            BoxStyle::Heavy => ('┏', '┓', '┗', '┛', '━', '┃'),
+            BoxStyle::Double => ('╔', '╗', '╚', '╝', '═', '║'),
+            BoxStyle::Rounded => ('╭', '╮', '╰', '╯', '─', '│')
+        }
+    }
+}
+
+/// The box `justify_boxed` draws around text
+pub struct Frame<'a> {
+    /// The characters to draw the box with.
+    pub style: BoxStyle,
+    /// The number of spaces between the sides of the box and the text.
+    pub padding: usize,
+    /// Shown in the top border, cut short if it doesn't fit. Empty for no title.
+    pub title: &'a str
+}
+
+impl<'a> Default for Frame<'a> {
+    fn default() -> Self {
+        Frame {
+            style: BoxStyle::Light,
+            padding: 1,
+            title: ""
+        }
+    }
+}
+
+/// The number of columns `s` is displayed in, which the box is drawn to fit: its width with
+/// `wcwidth`, and otherwise one column per character, like the borders, rather than the bytes
+/// lines are justified by.
+#[cfg(feature="unicode-width")]
+fn columns(s: &str, settings: &Settings) -> usize {
+    if settings.wcwidth { str_width(s, settings) } else { s.chars().count() }
+}
+
+#[cfg(not(feature="unicode-width"))]
+fn columns(s: &str, _settings: &Settings) -> usize {
+    s.chars().count()
+}
+
+/// The longest start of `s` that is at most `width` columns wide.
+fn truncate<'a>(s: &'a str, width: usize, settings: &Settings) -> &'a str {
+    let end = s.char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .take_while(|&i| columns(&s[..i], settings) <= width)
+        .last()
+        .unwrap_or(0);
+    &s[..end]
+}
+
+/// Justifies `text` inside the box described by `frame`. The box, including its borders, is
+/// `settings.width` columns wide; the text is justified to what is left after the borders and
+/// `frame.padding`, and every line is padded to that width. The borders are assumed to be one
+/// column wide whether or not `wcwidth` is used.
+pub fn justify_boxed(text: &str, settings: &Settings, frame: &Frame) -> String {
+    let (top_left, top_right, bottom_left, bottom_right, horizontal, vertical) = frame.style.chars();
+    let outer = settings.width.max(2 + 2 * frame.padding + 1);
+    let width = outer - 2 - 2 * frame.padding;
+    let inner_settings = Settings { width, ..settings.clone() };
+
+    let justified = justify(text, &inner_settings);
+    let justified = justified.strip_suffix(settings.newline).unwrap_or(&justified);
+    let padding = " ".repeat(frame.padding);
+
+    let mut top = top_left.to_string();
+    let mut rule = outer - 2;
+    // The title needs a space on each side and a horizontal line on each side of that.
+    let title = truncate(frame.title, rule.saturating_sub(4), settings).trim_end();
+    if !title.is_empty() {
+        top.push(horizontal);
+        top += &format!(" {} ", title);
+        rule -= 3 + columns(title, settings);
+    }
+    top.extend(std::iter::repeat_n(horizontal, rule));
+    top.push(top_right);
+
+    let mut lines = vec![top];
+    for line in justified.split(settings.newline) {
+        let fill = " ".repeat(width.saturating_sub(columns(line, settings)));
+        lines.push(format!("{}{}{}{}{}{}", vertical, padding, line, fill, padding, vertical));
+    }
+    let mut bottom = bottom_left.to_string();
+    bottom.extend(std::iter::repeat_n(horizontal, outer - 2));
+    bottom.push(bottom_right);
+    lines.push(bottom);
+
+    lines.join(settings.newline)
+}
@@ -0,0 +1,224 @@
+//! Building and validating `Settings`.
+
+use std::error::Error;
+use std::fmt;
+
+use {Dictionary, Direction, HyphenationExceptions, InsertAt, Language, Normalization, Settings, SingleWord};
+
+/// A combination of `Settings` that can't be justified with (returned by `Settings::validate`
+/// and `SettingsBuilder::build`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsError {
+    /// `width` is zero.
+    ZeroWidth,
+    /// `hyphenate_overflow` is on, but `hyphen` leaves no room on a line for the word being
+    /// hyphenated.
+    HyphenTooWide,
+    /// `hyphenate_overflow` is on, but `left_hyphen_min` or `right_hyphen_min` characters and
+    /// `hyphen` don't fit on a line together.
+    HyphenMinTooWide,
+    /// `ignore_spaces` is on without `hyphenate_overflow`, which would produce no output.
+    IgnoreSpacesWithoutHyphenation
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SettingsError::ZeroWidth => write!(f, "width must be at least 1"),
+            SettingsError::HyphenTooWide => write!(f, "hyphen must be narrower than width"),
+            SettingsError::HyphenMinTooWide =>
+                write!(f, "left_hyphen_min and right_hyphen_min must leave room for hyphen within width"),
+            SettingsError::IgnoreSpacesWithoutHyphenation =>
+                write!(f, "ignore_spaces requires hyphenate_overflow")
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+impl<'a> Settings<'a> {
+    /// Starts building `Settings` from the defaults. Unlike a struct literal, this works the same
+    /// whether or not the `unicode-width` feature is enabled.
+    pub fn builder() -> SettingsBuilder<'a> {
+        SettingsBuilder { settings: Settings::default() }
+    }
+
+    /// Checks that these settings can be justified with.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.width == 0 {
+            return Err(SettingsError::ZeroWidth);
+        }
+        if self.hyphenate_overflow && self.hyphen.len() >= self.width {
+            return Err(SettingsError::HyphenTooWide);
+        }
+        let hyphen_min = self.left_hyphen_min.max(self.right_hyphen_min);
+        if self.hyphenate_overflow && hyphen_min + self.hyphen.len() > self.width {
+            return Err(SettingsError::HyphenMinTooWide);
+        }
+        if self.ignore_spaces && !self.hyphenate_overflow {
+            return Err(SettingsError::IgnoreSpacesWithoutHyphenation);
+        }
+        Ok(())
+    }
+}
+
+/// Builds `Settings` one field at a time (see `Settings::builder`). Every setter has the same name
+/// as the field it sets.
+pub struct SettingsBuilder<'a> {
+    settings: Settings<'a>
+}
+
+impl<'a> SettingsBuilder<'a> {
+    /// See `Settings::justify_last_line`.
+    pub fn justify_last_line(mut self, justify_last_line: bool) -> Self {
+        self.settings.justify_last_line = justify_last_line;
+        self
+    }
+
+    /// See `Settings::hyphenate_overflow`.
+    pub fn hyphenate_overflow(mut self, hyphenate_overflow: bool) -> Self {
+        self.settings.hyphenate_overflow = hyphenate_overflow;
+        self
+    }
+
+    /// See `Settings::width`.
+    pub fn width(mut self, width: usize) -> Self {
+        self.settings.width = width;
+        self
+    }
+
+    /// See `Settings::insert_at`.
+    pub fn insert_at(mut self, insert_at: InsertAt<'a>) -> Self {
+        self.settings.insert_at = insert_at;
+        self
+    }
+
+    /// See `Settings::wcwidth`. Does nothing unless the `unicode-width` feature is enabled.
+    #[allow(unused_variables, unused_mut)]
+    pub fn wcwidth(mut self, wcwidth: bool) -> Self {
+        #[cfg(feature="unicode-width")] {
+            self.settings.wcwidth = wcwidth;
+        }
+        self
+    }
+
+    /// See `Settings::ambiguous_wide`.
+    pub fn ambiguous_wide(mut self, ambiguous_wide: bool) -> Self {
+        self.settings.ambiguous_wide = ambiguous_wide;
+        self
+    }
+
+    /// See `Settings::direction`.
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.settings.direction = direction;
+        self
+    }
+
+    /// See `Settings::visual_order`.
+    pub fn visual_order(mut self, visual_order: bool) -> Self {
+        self.settings.visual_order = visual_order;
+        self
+    }
+
+    /// See `Settings::normalization`.
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.settings.normalization = normalization;
+        self
+    }
+
+    /// See `Settings::preserve_form`.
+    pub fn preserve_form(mut self, preserve_form: bool) -> Self {
+        self.settings.preserve_form = preserve_form;
+        self
+    }
+
+    /// See `Settings::ignore_spaces`.
+    pub fn ignore_spaces(mut self, ignore_spaces: bool) -> Self {
+        self.settings.ignore_spaces = ignore_spaces;
+        self
+    }
+
+    /// See `Settings::newline`.
+    pub fn newline(mut self, newline: &'a str) -> Self {
+        self.settings.newline = newline;
+        self
+    }
+
+    /// See `Settings::hyphen`.
+    pub fn hyphen(mut self, hyphen: &'a str) -> Self {
+        self.settings.hyphen = hyphen;
+        self
+    }
+
+    /// See `Settings::separator`.
+    pub fn separator(mut self, separator: &'a str) -> Self {
+        self.settings.separator = separator;
+        self
+    }
+
+    /// See `Settings::single_word`.
+    pub fn single_word(mut self, single_word: SingleWord) -> Self {
+        self.settings.single_word = single_word;
+        self
+    }
+
+    /// See `Settings::min_last_line_words`.
+    pub fn min_last_line_words(mut self, min_last_line_words: usize) -> Self {
+        self.settings.min_last_line_words = min_last_line_words;
+        self
+    }
+
+    /// See `Settings::min_last_line_width`.
+    pub fn min_last_line_width(mut self, min_last_line_width: usize) -> Self {
+        self.settings.min_last_line_width = min_last_line_width;
+        self
+    }
+
+    /// See `Settings::kashida`.
+    pub fn kashida(mut self, kashida: bool) -> Self {
+        self.settings.kashida = kashida;
+        self
+    }
+
+    /// See `Settings::dictionary`.
+    pub fn dictionary(mut self, dictionary: &'a Dictionary) -> Self {
+        self.settings.dictionary = Some(dictionary);
+        self
+    }
+
+    /// See `Settings::language`.
+    pub fn language(mut self, language: Language) -> Self {
+        self.settings.language = language;
+        self
+    }
+
+    /// See `Settings::left_hyphen_min`.
+    pub fn left_hyphen_min(mut self, left_hyphen_min: usize) -> Self {
+        self.settings.left_hyphen_min = left_hyphen_min;
+        self
+    }
+
+    /// See `Settings::right_hyphen_min`.
+    pub fn right_hyphen_min(mut self, right_hyphen_min: usize) -> Self {
+        self.settings.right_hyphen_min = right_hyphen_min;
+        self
+    }
+
+    /// See `Settings::max_hyphenated_lines`.
+    pub fn max_hyphenated_lines(mut self, max_hyphenated_lines: usize) -> Self {
+        self.settings.max_hyphenated_lines = max_hyphenated_lines;
+        self
+    }
+
+    /// See `Settings::hyphenation_exceptions`.
+    pub fn hyphenation_exceptions(mut self, hyphenation_exceptions: &'a HyphenationExceptions) -> Self {
+        self.settings.hyphenation_exceptions = Some(hyphenation_exceptions);
+        self
+    }
+
+    /// Returns the settings, or why they can't be used (see `Settings::validate`).
+    pub fn build(self) -> Result<Settings<'a>, SettingsError> {
+        self.settings.validate()?;
+        Ok(self.settings)
+    }
+}
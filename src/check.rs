@@ -0,0 +1,62 @@
+//! Checking that text is already justified.
+
+use {Settings, justify};
+use unjustify::join_lines;
+
+/// A line of text that differs from what `justify` would produce, as found by `check_justified`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The 1-indexed number of the line.
+    pub line: usize,
+    /// What the line should be, or `None` if it should not be there at all.
+    pub expected: Option<String>,
+    /// What the line is, or `None` if the paragraph is missing lines.
+    pub found: Option<String>
+}
+
+/// Splits `text` into paragraphs, i.e. runs of lines that aren't blank. Returns, for each, the
+/// 0-indexed number of its first line and its lines.
+pub(crate) fn paragraphs<'a>(text: &'a str, settings: &Settings) -> Vec<(usize, Vec<&'a str>)> {
+    let mut ret = Vec::new();
+    let mut current: Option<(usize, Vec<&str>)> = None;
+
+    for (i, line) in text.split(settings.newline).enumerate() {
+        if line.trim().is_empty() {
+            ret.extend(current.take());
+        } else {
+            current.get_or_insert_with(|| (i, Vec::new())).1.push(line);
+        }
+    }
+    ret.extend(current);
+
+    ret
+}
+
+/// Checks  that  `text`  is already justified according to `settings`,  the  way  the
+/// `justify` binary would justify it: paragraphs are separated by blank lines, and
+/// the  lines  of each are joined (as by `unjustify`) and re-justified  as  one.
+/// Returns every line that differs, in order; if there are none, the text is
+/// justified.
+pub fn check_justified(text: &str, settings: &Settings) -> Vec<Mismatch> {
+    let mut ret = Vec::new();
+
+    for (start, lines) in paragraphs(text, settings) {
+        let joined = join_lines(&lines, settings);
+        let justified = justify(&joined, settings);
+        let justified = justified.strip_suffix(settings.newline).unwrap_or(&justified);
+        let expected: Vec<&str> = justified.split(settings.newline).collect();
+
+        for i in 0..lines.len().max(expected.len()) {
+            let (e, f) = (expected.get(i), lines.get(i));
+            if e != f {
+                ret.push(Mismatch {
+                    line: start + i + 1,
+                    expected: e.map(|s| s.to_string()),
+                    found: f.map(|s| s.to_string())
+                });
+            }
+        }
+    }
+
+    ret
+}
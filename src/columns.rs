@@ -0,0 +1,78 @@
+//! Laying justified text out in several columns side by side.
+
+use {Settings, justify, str_width};
+
+/// How `justify_columns` lays out columns
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Columns {
+    /// The number of columns side by side. 0 is treated as 1.
+    pub count: usize,
+    /// The number of spaces between two columns.
+    pub gutter: usize,
+    /// Whether the columns should be about the same height, no column more than one line
+    /// longer than another, rather than all but the last being full.
+    pub balance: bool
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns {
+            count: 2,
+            gutter: 2,
+            balance: false
+        }
+    }
+}
+
+/// How many lines go in each of `columns` columns holding `lines` lines in total.
+fn heights(lines: usize, columns: usize, balance: bool) -> Vec<usize> {
+    if balance {
+        (0..columns).map(|i| lines / columns + if i < lines % columns { 1 } else { 0 }).collect()
+    } else {
+        let height = lines.div_ceil(columns);
+        (0..columns).map(|i| lines.saturating_sub(i * height).min(height)).collect()
+    }
+}
+
+/// Justifies `text` into `layout.count` columns of equal width, newspaper style:  the
+/// first  column  is  filled from top to bottom, then the second, and  so  on.  The
+/// columns are separated by `layout.gutter` spaces and together take up `settings.width`;
+/// each  is  as  wide as the rest allows. Lines are padded to the  width  of  their
+/// column, except in the last one. Columns never start with the blank line  between
+/// two paragraphs.
+pub fn justify_columns(text: &str, settings: &Settings, layout: &Columns) -> String {
+    let columns = layout.count.max(1);
+    let gutter = layout.gutter;
+    let width = (settings.width.saturating_sub(gutter * (columns - 1)) / columns).max(1);
+    let column_settings = Settings { width, ..settings.clone() };
+
+    let justified = justify(text, &column_settings);
+    let justified = justified.strip_suffix(settings.newline).unwrap_or(&justified);
+    let lines: Vec<&str> = if justified.is_empty() { Vec::new() } else { justified.split(settings.newline).collect() };
+
+    let mut start = 0;
+    let mut split: Vec<&[&str]> = Vec::with_capacity(columns);
+    for height in heights(lines.len(), columns, layout.balance) {
+        let column = &lines[start..start+height];
+        // Don't start a column with the blank line between two paragraphs.
+        split.push(if start > 0 && column.first() == Some(&"") { &column[1..] } else { column });
+        start += height;
+    }
+
+    let mut rows = Vec::with_capacity(split[0].len());
+    for i in 0..split.iter().map(|c| c.len()).max().unwrap_or(0) {
+        let mut row = String::new();
+        for (c, column) in split.iter().enumerate() {
+            let line = column.get(i).unwrap_or(&"");
+            row += line;
+            if c < columns - 1 {
+                let pad = width.saturating_sub(str_width(line, settings)) + gutter;
+                row.extend(std::iter::repeat_n(' ', pad));
+            }
+        }
+        row.truncate(row.trim_end_matches(' ').len());
+        rows.push(row);
+    }
+
+    rows.join(settings.newline)
+}
@@ -0,0 +1,233 @@
+//! Deciding where the spaces added to a line go.
+
+use std::sync::Mutex;
+
+use InsertAt;
+
+/// A line that spaces are being added to, as given to a `SpaceDistributor`.
+pub struct Line<'a> {
+    /// The words of the line. Spaces are inserted between them.
+    pub words: &'a [&'a str],
+    /// The width in columns of each of `words`. Every word but the last includes the single space
+    /// that already follows it, if there is one (see `Settings::dictionary`).
+    pub widths: &'a [usize],
+    /// The total number of spaces that must be inserted.
+    pub spaces: usize,
+    /// The 0-indexed number of the line within its paragraph.
+    pub number: usize,
+    /// The 0-indexed number of the paragraph within the text passed to `justify`.
+    pub paragraph: usize,
+    /// How many spaces went into each gap of the previous line of the paragraph, if any.
+    pub previous: Option<&'a [usize]>,
+    /// The 0-indexed columns of the spaces between words on the previous line of the paragraph.
+    pub previous_columns: &'a [usize]
+}
+
+impl<'a> Line<'a> {
+    /// The number of gaps between words, i.e. places spaces can be inserted.
+    pub fn gaps(&self) -> usize {
+        self.words.len().saturating_sub(1)
+    }
+}
+
+/// A stateful way of distributing spaces (use with `InsertAt::Distributor`).
+///
+/// Unlike `InsertAt::Custom`, a distributor is called once per line and may keep state between
+/// calls, e.g. a random number generator.
+pub trait SpaceDistributor {
+    /// Returns the number of spaces to insert in each gap of `line`. The returned vector must
+    /// have `line.gaps()` elements summing to `line.spaces`; if it does not, the line is padded
+    /// as if `InsertAt::Balanced` were used instead.
+    fn distribute(&mut self, line: &Line) -> Vec<usize>;
+}
+
+/// Inserts  spaces  at pseudo-random gaps. Every gap first receives its even  share
+/// of  the spaces, and the remaining spaces go to distinct randomly chosen gaps, so
+/// no gap is ever more than one space wider than another. The same seed always
+/// produces the same output.
+pub struct SeededRandom {
+    state: u64
+}
+
+impl SeededRandom {
+    /// Creates a distributor whose choices are determined entirely by `seed`.
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on 0, so mix the seed with a constant first.
+        SeededRandom { state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    /// xorshift64*
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Default for SeededRandom {
+    fn default() -> Self {
+        SeededRandom::new(0)
+    }
+}
+
+/// Scrambles `x`, so that similar inputs give unrelated outputs.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl SpaceDistributor for SeededRandom {
+    fn distribute(&mut self, line: &Line) -> Vec<usize> {
+        let gaps = line.gaps();
+        if gaps == 0 { return Vec::new() }
+        let mut add_v = vec![line.spaces / gaps; gaps];
+        let mut order: Vec<usize> = (0..gaps).collect();
+
+        // Partial Fisher-Yates shuffle; only the first `remainder` gaps matter.
+        for i in 0..line.spaces % gaps {
+            let j = i + (self.next() % (gaps - i) as u64) as usize;
+            order.swap(i, j);
+            add_v[order[i]] += 1;
+        }
+
+        add_v
+    }
+}
+
+fn left(add: usize, gaps: usize) -> Vec<usize> {
+    let mut add_v = vec![0; gaps];
+
+    for j in (1..gaps+1).cycle().take(add) {
+        add_v[j-1] += 1;
+    }
+
+    add_v
+}
+
+fn right(add: usize, gaps: usize) -> Vec<usize> {
+    let mut add_v = vec![0; gaps];
+
+    for j in (1..gaps+1).rev().cycle().take(add) {
+        add_v[j-1] += 1;
+    }
+
+    add_v
+}
+
+fn balanced(add: usize, gaps: usize) -> Vec<usize> {
+    let mut add_v = vec![0; gaps];
+
+    for j in (1..gaps+1).cycle().take(add) {
+        if j % 2 == 0 { //EVEN
+            add_v[gaps - (j/2)] += 1;
+        } else { //ODD
+            add_v[j/2] += 1;
+        }
+    }
+
+    add_v
+}
+
+/// The number of columns of `line`, padded per `add_v`, that hold a space between words and also
+/// hold one on the previous line.
+fn river_cost(line: &Line, add_v: &[usize]) -> usize {
+    let mut column = 0;
+    let mut cost = 0;
+
+    for (i, add) in add_v.iter().enumerate() {
+        column += line.widths.get(i).unwrap_or(&0);
+        // The word's own trailing space is part of the gap as well.
+        let start = if line.words[i].ends_with(char::is_whitespace) { column.saturating_sub(1) } else { column };
+        for c in start..column+add {
+            if line.previous_columns.binary_search(&c).is_ok() {
+                cost += 1;
+            }
+        }
+        column += add;
+    }
+
+    cost
+}
+
+/// Gives every gap its even share, then hands the remaining spaces out one at a time to whichever
+/// gap lines up the fewest gaps with the previous line, preferring the `Balanced` order on ties.
+fn avoid_rivers(line: &Line) -> Vec<usize> {
+    let gaps = line.gaps();
+    let mut add_v = vec![line.spaces / gaps; gaps];
+    let mut free: Vec<usize> = (1..gaps+1)
+        .map(|j| if j % 2 == 0 { gaps - j/2 } else { j/2 })
+        .collect();
+
+    for _ in 0..line.spaces % gaps {
+        let (k, _) = free.iter()
+            .enumerate()
+            .min_by_key(|&(_, &g)| {
+                add_v[g] += 1;
+                let cost = river_cost(line, &add_v);
+                add_v[g] -= 1;
+                cost
+            })
+            .unwrap();
+        add_v[free.remove(k)] += 1;
+    }
+
+    add_v
+}
+
+fn from_distributor(distributor: &Mutex<dyn SpaceDistributor + Send + '_>, line: &Line) -> Vec<usize> {
+    // A distributor that panicked on another thread is still usable for our purposes.
+    let mut distributor = distributor.lock().unwrap_or_else(|e| e.into_inner());
+    let add_v = distributor.distribute(line);
+
+    if add_v.len() == line.gaps() && add_v.iter().sum::<usize>() == line.spaces {
+        add_v
+    } else {
+        balanced(line.spaces, line.gaps())
+    }
+}
+
+/// Decides how many spaces go into each of the gaps of `line`.
+pub(crate) fn distribute(line: &Line, insert_at: &InsertAt) -> Vec<usize> {
+    let add = line.spaces;
+    let gaps = line.gaps();
+    let mut add_v = vec![0; gaps];
+
+    match *insert_at {
+        InsertAt::Left => {
+            add_v = left(add, gaps);
+        },
+        InsertAt::Right => {
+            add_v = right(add, gaps);
+        },
+        InsertAt::Alternating => {
+            add_v = if line.number % 2 == 0 { left(add, gaps) } else { right(add, gaps) };
+        },
+        InsertAt::Balanced => {
+            add_v = balanced(add, gaps);
+        },
+        InsertAt::AvoidRivers => {
+            add_v = avoid_rivers(line);
+        },
+        InsertAt::Custom(f) => {
+            let words = line.words.to_vec();
+            for j in 0..add {
+                match add_v.get_mut(f(j, add, gaps, &words)) {
+                    Some(n) => *n += 1,
+                    None => return balanced(add, gaps)
+                }
+            }
+        },
+        InsertAt::StatelessRandom(seed) => {
+            let seed = splitmix64(seed ^ splitmix64((line.paragraph as u64) << 32 ^ line.number as u64));
+            add_v = SeededRandom::new(seed).distribute(line);
+        },
+        InsertAt::Distributor(d) => {
+            add_v = from_distributor(d, line);
+        }
+    }
+
+    add_v
+}
@@ -0,0 +1,119 @@
+//! Where `hyphenate_overflow` may break words.
+
+use std::collections::HashMap;
+
+use Settings;
+
+/// Words hyphenated differently from the rest: only at the places given, or not at all (use with
+/// `Settings::hyphenation_exceptions`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HyphenationExceptions {
+    /// Each word, lowercase, and the number of characters before each place it may be broken.
+    words: HashMap<String, Vec<usize>>
+}
+
+impl HyphenationExceptions {
+    /// The exceptions `words`, in which hyphens mark the places they may be broken, e.g.
+    /// `ta-ble`. A word without hyphens is never broken.
+    pub fn new<I: IntoIterator<Item=S>, S: AsRef<str>>(words: I) -> Self {
+        let mut exceptions = HyphenationExceptions::default();
+        for word in words {
+            exceptions.insert(word.as_ref());
+        }
+        exceptions
+    }
+
+    /// The exceptions in `list`, one per line as for `new`. Blank lines and lines starting with
+    /// `#` are skipped.
+    pub fn from_list(list: &str) -> Self {
+        HyphenationExceptions::new(list.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#')))
+    }
+
+    /// Adds `word`, in which hyphens mark the places it may be broken, replacing any exception
+    /// for the same word.
+    pub fn insert(&mut self, word: &str) {
+        let mut breaks = Vec::new();
+        let mut letters = String::with_capacity(word.len());
+        for c in word.chars() {
+            if c == '-' {
+                breaks.push(letters.chars().count());
+            } else {
+                letters.extend(c.to_lowercase());
+            }
+        }
+        self.words.insert(letters, breaks);
+    }
+
+    /// The places `word` may be broken, as numbers of characters before them, if it is an
+    /// exception. Case is ignored.
+    pub fn breaks(&self, word: &str) -> Option<&[usize]> {
+        self.words.get(&word.to_lowercase()).map(|b| &b[..])
+    }
+}
+
+/// Where to hyphenate `units`, a word split into characters that are `widths` wide, so that no
+/// piece is wider than `limit`: the indices of the units the pieces start at, the first being 0.
+/// Each piece is broken as near its end as `settings` allows, or, if it allows no break there, at
+/// the next place it does, which makes the piece too wide.
+pub(crate) fn points(units: &[&str], widths: &[usize], limit: usize, settings: &Settings) -> Vec<usize> {
+    let allowed = allowed(units, settings);
+    let mut points = vec![0];
+    let mut q = 0;
+    let mut i = 0;
+
+    while i < units.len() {
+        q += widths[i];
+        let start = points[points.len()-1];
+        if q > limit && i > start {
+            if settings.max_hyphenated_lines != 0 && points.len() > settings.max_hyphenated_lines {
+                break
+            }
+            let before = (start+1..=i).rev().find(|&b| allowed[b]);
+            let b = match before.or_else(|| (i+1..units.len()).find(|&b| allowed[b])) {
+                Some(b) => b,
+                None => break
+            };
+            points.push(b);
+            if b > i {
+                q = 0;
+                i = b;
+                continue
+            }
+            q = widths[b..=i].iter().sum();
+        }
+        i += 1;
+    }
+
+    points
+}
+
+/// Whether `units` may be hyphenated before each of them.
+fn allowed(units: &[&str], settings: &Settings) -> Vec<bool> {
+    let n = units.len();
+    let mut allowed: Vec<bool> = (0..n)
+        .map(|b| b > 0
+            && b >= settings.left_hyphen_min
+            && n - b >= settings.right_hyphen_min
+            && settings.language.may_break(units[b-1], units[b]))
+        .collect();
+
+    if let Some(exceptions) = settings.hyphenation_exceptions {
+        // Punctuation around the word, e.g. a comma after it, isn't part of the exception.
+        let is_letter = |u: &&str| u.chars().any(char::is_alphanumeric);
+        let first = units.iter().position(is_letter).unwrap_or(n);
+        let last = units.iter().rposition(is_letter).map_or(first, |l| l + 1);
+        if let Some(breaks) = exceptions.breaks(&units[first..last].concat()) {
+            let mut chars = 0;
+            for b in 0..n {
+                allowed[b] = b > first && b < last && breaks.contains(&chars);
+                if b >= first {
+                    chars += units[b].chars().count();
+                }
+            }
+        }
+    }
+
+    allowed
+}
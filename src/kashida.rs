@@ -0,0 +1,119 @@
+//! Justifying Arabic text by elongating words with kashidas (U+0640 ARABIC TATWEEL).
+
+use {Settings, char_width};
+
+const TATWEEL: char = '\u{640}';
+
+/// Arabic harakat and other marks, which sit on the letter before them.
+pub(crate) fn is_mark(c: char) -> bool {
+    matches!(c, '\u{610}'..='\u{61A}' | '\u{64B}'..='\u{65F}' | '\u{670}' | '\u{6D6}'..='\u{6DC}'
+        | '\u{6DF}'..='\u{6E4}' | '\u{6E7}' | '\u{6E8}' | '\u{6EA}'..='\u{6ED}')
+}
+
+/// Letters that join to the letters on both sides of them.
+fn is_dual_joining(c: char) -> bool {
+    matches!(c, '\u{626}' | '\u{628}' | '\u{62A}'..='\u{62E}' | '\u{633}'..='\u{63F}'
+        | '\u{641}'..='\u{647}' | '\u{649}' | '\u{64A}' | '\u{66E}' | '\u{66F}' | '\u{678}'..='\u{687}'
+        | '\u{69A}'..='\u{6BF}' | '\u{6C1}' | '\u{6C2}' | '\u{6CC}' | '\u{6CE}' | '\u{6D0}' | '\u{6D1}')
+}
+
+/// Letters that only join to the letter before them, e.g. Alef and Waw.
+fn is_right_joining(c: char) -> bool {
+    matches!(c, '\u{622}'..='\u{625}' | '\u{627}' | '\u{629}' | '\u{62F}'..='\u{632}' | '\u{648}'
+        | '\u{671}'..='\u{673}' | '\u{675}'..='\u{677}' | '\u{688}'..='\u{699}' | '\u{6C0}'
+        | '\u{6C3}'..='\u{6CB}' | '\u{6CD}' | '\u{6CF}' | '\u{6D2}' | '\u{6D3}')
+}
+
+/// Whether `after` is an Alef that forms the Lam-Alef ligature with a Lam before it.
+fn is_lam_alef(before: char, after: char) -> bool {
+    before == '\u{644}' && matches!(after, '\u{622}' | '\u{623}' | '\u{625}' | '\u{627}')
+}
+
+/// How good a place for a kashida the join between `before` and `after` is, `after` being in its
+/// final form if `last`. Lower is better. The order follows the usual rules for Arabic
+/// calligraphy, as also used by e.g. Microsoft's text layout.
+fn priority(before: char, after: char, last: bool) -> u8 {
+    match (before, after) {
+        // After Seen, Sheen, Sad and Dad.
+        ('\u{633}'..='\u{636}', _) => 1,
+        // Before a final Teh Marbuta, Heh, Dal or Thal.
+        (_, '\u{629}' | '\u{647}' | '\u{62F}' | '\u{630}') if last => 2,
+        // Before a final Alef, Tah, Zah, Lam, Kaf or Gaf.
+        (_, '\u{627}' | '\u{637}' | '\u{638}' | '\u{644}' | '\u{643}' | '\u{6A9}' | '\u{6AF}') if last => 3,
+        // Between a medial Beh-like letter and a final Reh, Zain, Yeh or Alef Maksura.
+        ('\u{626}' | '\u{628}' | '\u{62A}' | '\u{62B}' | '\u{646}' | '\u{64A}' | '\u{67E}',
+         '\u{631}' | '\u{632}' | '\u{649}' | '\u{64A}') if last => 4,
+        // Before a final Waw, Ain, Qaf or Feh.
+        (_, '\u{648}' | '\u{639}' | '\u{642}' | '\u{641}') if last => 5,
+        _ => 6
+    }
+}
+
+/// The best place in `word` for a kashida, as (priority, byte index), if it has one. Ties go to
+/// the place nearest the end of the word. The name of God is never elongated, and neither is the
+/// Lam-Alef ligature, whose letters can't be pulled apart.
+fn best_point(word: &str) -> Option<(u8, usize)> {
+    // Each letter, and the byte index just after it and its marks.
+    let mut letters: Vec<(char, usize, usize)> = Vec::new();
+    for (i, c) in word.char_indices() {
+        match letters.last_mut() {
+            Some(last) if is_mark(c) => last.2 = i + c.len_utf8(),
+            _ => letters.push((c, i, i + c.len_utf8()))
+        }
+    }
+
+    let bare: String = letters.iter().map(|&(c, _, _)| c).collect();
+    if bare.contains("\u{627}\u{644}\u{644}\u{647}") || bare.contains("\u{671}\u{644}\u{644}\u{647}") {
+        return None
+    }
+
+    let mut best: Option<(u8, usize)> = None;
+    for k in 1..letters.len() {
+        let (before, _, end) = letters[k-1];
+        let after = letters[k].0;
+        if !is_dual_joining(before) || !(is_dual_joining(after) || is_right_joining(after))
+            || is_lam_alef(before, after) {
+            continue
+        }
+        let last = !is_dual_joining(after) || letters.get(k+1)
+            .is_none_or(|&(next, _, _)| !is_dual_joining(next) && !is_right_joining(next));
+        let p = priority(before, after, last);
+        if best.is_none_or(|(b, _)| p <= b) {
+            best = Some((p, end));
+        }
+    }
+
+    best
+}
+
+/// Inserts kashidas into `words` to take up as many as possible of the `spaces` columns that are
+/// to be added to a line, at most one per word, at the best place in each word. Words with the
+/// best places get theirs first. Returns the words and the columns that are still left over.
+pub(crate) fn elongate(words: &[&str], spaces: usize, settings: &Settings) -> (Vec<String>, usize) {
+    let width = char_width(TATWEEL, settings);
+    let mut points: Vec<(u8, usize, usize)> = words.iter()
+        .enumerate()
+        // A word that already has a kashida is left alone.
+        .filter(|&(_, word)| !word.contains(TATWEEL))
+        .filter_map(|(w, word)| best_point(word).map(|(p, i)| (p, w, i)))
+        .collect();
+    points.sort();
+
+    let mut at: Vec<Option<usize>> = vec![None; words.len()];
+    let mut left = spaces;
+    for (_, w, i) in points {
+        if width == 0 || left < width { break }
+        at[w] = Some(i);
+        left -= width;
+    }
+
+    let words = words.iter()
+        .zip(at)
+        .map(|(word, i)| match i {
+            Some(i) => format!("{}{}{}", &word[..i], TATWEEL, &word[i..]),
+            None => word.to_string()
+        })
+        .collect();
+
+    (words, left)
+}
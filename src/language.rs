@@ -0,0 +1,127 @@
+//! Typographic rules that depend on the language of the text.
+
+use std::borrow::Cow;
+
+/// U+202F NARROW NO-BREAK SPACE, which French puts before some punctuation.
+const THIN: char = '\u{202F}';
+
+/// The language text is written in, for its typographic rules (use with `Settings`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(rename_all="snake_case"))]
+pub enum Language {
+    /// Default; no rules beyond not breaking lines at no-break spaces.
+    None,
+    /// A thin no-break space is put before `;`, `:`, `!`, `?` and `»` and after `«`, in place of
+    /// a space if there is one.
+    French,
+    /// Single-letter prepositions and conjunctions (a, i, k, o, s, u, v, z) don't end a line.
+    Czech,
+    /// Single-letter prepositions and conjunctions (a, i, o, u, w, z) don't end a line.
+    Polish,
+    /// Single-letter prepositions and conjunctions (a, i, k, o, s, u, v, z) don't end a line.
+    Slovak,
+    /// Words aren't hyphenated between the letters of "ck", and letter-spacing keeps them
+    /// together.
+    German,
+    /// Words aren't hyphenated between the letters of "ij", which is a single letter, and
+    /// letter-spacing keeps them together.
+    Dutch
+}
+
+impl Language {
+    /// `text`, a paragraph, with the spaces the language wants added.
+    pub(crate) fn prepare(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Language::French => Cow::Owned(french(text)),
+            _ => Cow::Borrowed(text)
+        }
+    }
+
+    /// Whether `word`, with the space after it, may not end a line and goes on the next with the
+    /// word after it.
+    pub(crate) fn binds_to_next(self, word: &str) -> bool {
+        let letters = match self {
+            Language::Czech | Language::Slovak => "aiksouvz",
+            Language::Polish => "aiouwz",
+            _ => return false
+        };
+        let mut chars = word.trim_end().chars().flat_map(char::to_lowercase);
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => letters.contains(c),
+            _ => false
+        }
+    }
+
+    /// Whether a word may be hyphenated, or letter-spaced, between `before` and `after`.
+    pub(crate) fn may_break(self, before: &str, after: &str) -> bool {
+        let pair = (before.chars().next_back(), after.chars().next());
+        match self {
+            Language::German => !matches!(pair, (Some('c'), Some('k')) | (Some('C'), Some('K'))),
+            Language::Dutch => !matches!(pair, (Some('i'), Some('j')) | (Some('I'), Some('J'))),
+            _ => true
+        }
+    }
+
+    /// Joins those of `units`, which make up `word`, that `may_break` doesn't allow to be
+    /// separated.
+    pub(crate) fn join<'a>(self, word: &'a str, units: Vec<&'a str>) -> Vec<&'a str> {
+        let mut ret: Vec<&str> = Vec::with_capacity(units.len());
+        let mut offset = 0;
+
+        for unit in units {
+            match ret.last_mut() {
+                Some(last) if !self.may_break(last, unit) => {
+                    *last = &word[offset-last.len()..offset+unit.len()];
+                },
+                _ => ret.push(unit)
+            }
+            offset += unit.len();
+        }
+
+        ret
+    }
+}
+
+/// Whether French wants a thin space between `previous`, the character before, and punctuation
+/// written straight after it, which is followed by `next`. Times like 10:30 and addresses like
+/// http://… are left alone.
+fn wants_space(previous: Option<char>, next: Option<char>) -> bool {
+    previous.is_some_and(|p| p.is_alphanumeric() || matches!(p, ')' | ']' | '»'))
+        && next.is_none_or(|n| n.is_whitespace() || matches!(n, ';' | ':' | '!' | '?' | '»' | ')'))
+}
+
+/// `text` with French spacing around punctuation.
+fn french(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut ret = String::with_capacity(text.len() + text.len() / 8);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '«' => {
+                ret.push(c);
+                let mut j = i + 1;
+                while j < chars.len() && chars[j].is_whitespace() { j += 1 }
+                if j < chars.len() { ret.push(THIN) }
+                i = j;
+                continue
+            },
+            ';' | ':' | '!' | '?' | '»' => {
+                let trimmed = ret.trim_end_matches(char::is_whitespace).len();
+                let previous = ret[..trimmed].chars().next_back();
+                let spaced = trimmed < ret.len() && previous.is_some();
+                if spaced || (c == '»' && previous.is_some()) || wants_space(previous, chars.get(i+1).copied()) {
+                    ret.truncate(trimmed);
+                    ret.push(THIN);
+                }
+                ret.push(c);
+            },
+            _ => ret.push(c)
+        }
+        i += 1;
+    }
+
+    ret
+}
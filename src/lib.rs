@@ -55,11 +55,64 @@
 //! and  Latin  letters  take one, it's possible for there to be an  odd  number  of
 //! characters  on  a line to be justified. Also, depending on your browser, it  may
 //! not look right, try pasting it into a terminal emulator.
+//!
+//! With  the `serde` feature, `SettingsBuf`, an owned version of `Settings`, can be
+//! serialized  and deserialized, e.g. to accept settings from a configuration  file
+//! or  an API request. Fields that are missing take their default values. Borrow it
+//! as `Settings` with `SettingsBuf::settings`.
+//!
+//! With  the  `bidi` feature, right-to-left text such as Hebrew and Arabic  can  be
+//! justified  too:  set  `direction`  in  `Settings`  to  `Direction::Rtl`,  or  to
+//! `Direction::Auto`  to decide for each paragraph as in the Unicode  Bidirectional
+//! Algorithm. Lines are broken in logical order and, with `visual_order`, output in
+//! the order they are displayed in.
+//!
+//! With  the `normalization` feature, text can be normalized to NFC or NFKC  before
+//! it  is  measured, by setting `normalization` in `Settings`, so  that  decomposed
+//! text,  e.g. from macOS file names, is measured and hyphenated like the same text
+//! precomposed. With `preserve_form`, the output keeps the form of the input.
+
+#![allow(clippy::len_zero, clippy::ptr_arg, clippy::needless_borrow, clippy::unnecessary_map_or)]
 
 #[cfg(feature="unicode-width")] extern crate unicode_width;
 #[cfg(feature="unicode-width")] use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+#[cfg(feature="serde")] #[macro_use] extern crate serde;
+#[cfg(feature="bidi")] extern crate unicode_bidi;
+#[cfg(feature="normalization")] extern crate unicode_normalization;
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+mod distribute;
+pub use distribute::{Line, SpaceDistributor, SeededRandom};
+mod rivers;
+pub use rivers::{River, find_rivers};
+mod check;
+pub use check::{Mismatch, check_justified};
+mod unjustify;
+pub use unjustify::unjustify;
+mod owned;
+pub use owned::{InsertAtBuf, SettingsBuf};
+mod builder;
+pub use builder::{SettingsBuilder, SettingsError};
+mod columns;
+pub use columns::{Columns, justify_columns};
+mod paginate;
+pub use paginate::{Pagination, paginate};
+mod boxed;
+pub use boxed::{BoxStyle, Frame, justify_boxed};
+#[cfg(feature="bidi")] mod bidi;
+mod kashida;
+#[cfg(feature="normalization")] mod normalize;
+mod segment;
+pub use segment::Dictionary;
+mod language;
+pub use language::Language;
+mod hyphenation;
+pub use hyphenation::HyphenationExceptions;
 
 /// Where to insert spaces (use with `Settings`)
+#[derive(Clone, Copy)]
 pub enum InsertAt<'a> {
     /// Spaces are added starting at the left.
     Left,
@@ -69,16 +122,86 @@ pub enum InsertAt<'a> {
     /// space  goes in place 1, the second space in place 5, the third space  in
     /// place 2, fourth space in place 4, etc.
     Balanced,
+    /// Alternates between `Left` on even-numbered lines of a paragraph and `Right` on
+    /// odd-numbered  ones, which helps avoid gaps lining up into "rivers"  on  top
+    /// of each other.
+    Alternating,
+    /// Each  line's  extra  spaces are put where they line up  with  as  few  of  the
+    /// previous  line's gaps as possible, to avoid vertical "rivers" of  whitespace
+    /// running through the text. See also `find_rivers`.
+    AvoidRivers,
+    /// Like  `SeededRandom`,  but without state: the extra spaces of each  line
+    /// go  to  gaps chosen by the seed, the paragraph number and the  line  number,
+    /// so the same text always comes out the same.
+    StatelessRandom(u64),
     /// The function receives the current 0-indexed iteration in position 1, the
     /// total number of spaces to be added in position 2, the number of possible
     /// entry  points in position 3, and the line being justified in position 4.
     /// This  could  be used, for example, to implement insertion of  spaces  at
     /// random  points. If using this, you may not need every argument, but they
-    /// are provided anyway for maximum extensibility.
-    Custom(&'a dyn Fn(usize, usize, usize, &Vec<&str>)->usize)
+    /// are provided anyway for maximum extensibility. If the function returns an
+    /// out-of-range index, the line is padded as with `Balanced` instead.
+    Custom(&'a CustomInsertAt<'a>),
+    /// The  distributor is called once per line and decides how many spaces  go
+    /// in  every  gap.  Unlike `Custom`, it may keep mutable  state,  and  it  is
+    /// `Send`, so the same distributor can be used by `Settings` on several threads.
+    /// See e.g. `SeededRandom`.
+    Distributor(&'a Mutex<dyn SpaceDistributor + Send + 'a>)
+}
+
+/// The signature of the function used by `InsertAt::Custom`.
+pub type CustomInsertAt<'a> = dyn Fn(usize, usize, usize, &Vec<&str>)->usize + 'a;
+
+/// What to do with a line that consists of a single word, and so has no gaps between words to
+/// stretch (use with `Settings`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(rename_all="snake_case"))]
+pub enum SingleWord {
+    /// Default; the word is left as is, so the line does not reach the right edge.
+    Unpadded,
+    /// Padding  is  added  between  the  characters  of  the  word  (letter-spacing),  in  the
+    /// pattern given by `Settings::insert_at`. Combining characters are kept with their base.
+    LetterSpacing,
+    /// The word is padded on the right with the given character, e.g. `'-'` or `'.'`. The
+    /// character is assumed to be one column wide.
+    Fill(char)
+}
+
+/// The direction text is written in (use with `Settings`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(rename_all="snake_case"))]
+pub enum Direction {
+    /// Default; left to right.
+    Ltr,
+    /// Right to left, e.g. Hebrew or Arabic.
+    Rtl,
+    /// Decided for each paragraph by its first strong character, as in the Unicode
+    /// Bidirectional Algorithm (UAX #9).
+    Auto
+}
+
+/// The Unicode normalization form text is put in (use with `Settings`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(rename_all="snake_case"))]
+pub enum Normalization {
+    /// Default; text is measured as it is.
+    None,
+    /// Canonical composition: decomposed letters, e.g. an "e" followed by a combining acute
+    /// accent, are composed into one character where possible.
+    Nfc,
+    /// Compatibility composition: like `Nfc`, but compatibility characters such as ligatures and
+    /// fullwidth forms are also replaced by their plain equivalents.
+    Nfkc
 }
 
 /// Settings used by `justify` and `justify_paragraph`
+///
+/// As `InsertAt::Custom` may borrow a function that isn't `Sync`, `Settings` can't be shared
+/// between threads; send a `SettingsBuf` instead, or make `Settings` on each thread.
+#[derive(Clone)]
 pub struct Settings<'a> {
     /// Whether the last line should also be justified. Can result in weird output if the last line
     /// contains very few words.
@@ -92,6 +215,27 @@ pub struct Settings<'a> {
     #[cfg(feature="unicode-width")]
     /// On unicode text, attempt to use wcwidth
     pub wcwidth: bool,
+    /// With `wcwidth`, count characters whose East Asian Width is Ambiguous (such as "·", "—",
+    /// "°" and box drawing) as two columns wide, as terminals in CJK locales display them,
+    /// rather than one. Ignored unless the `unicode-width` feature is enabled.
+    pub ambiguous_wide: bool,
+    /// The direction of each paragraph. Lines of right-to-left paragraphs that don't reach `width`
+    /// (such as the last) are flush with the right edge, and padded on the left. Ignored unless
+    /// the `bidi` feature is enabled.
+    pub direction: Direction,
+    /// Whether lines are output in visual order, i.e. the order they are displayed in, rather
+    /// than logical order. Useful for terminals that don't implement bidirectional text. Ignored
+    /// unless the `bidi` feature is enabled.
+    pub visual_order: bool,
+    /// The form text is normalized to before it is measured, so that e.g. decomposed text (as
+    /// in macOS file names) is measured like the same text precomposed. Without `wcwidth`,
+    /// normalized text is measured in characters rather than bytes, combining marks taking no
+    /// room. The output is in this form too, unless `preserve_form`. Ignored unless the
+    /// `normalization` feature is enabled.
+    pub normalization: Normalization,
+    /// Whether the output keeps the form of the input, `normalization` only being used to
+    /// measure it.
+    pub preserve_form: bool,
     /// This feature is sometimes useful with CJK text in conjunction with hyphenate_overflow. When
     /// on, spaces are not considered when justifying text.
     pub ignore_spaces: bool,
@@ -101,7 +245,36 @@ pub struct Settings<'a> {
     /// The hyphen that should be used if `hyphenate_overflow` is true
     pub hyphen: &'a str,
     /// The separator between paragraphs when `justify` is called
-    pub separator: &'a str
+    pub separator: &'a str,
+    /// How lines containing only one word are padded to `width`.
+    pub single_word: SingleWord,
+    /// The fewest words the last line of a paragraph should contain. If it has fewer, the lines
+    /// before it are broken again, narrower, so that words move down onto it, as long as each of
+    /// them keeps at least two words.
+    pub min_last_line_words: usize,
+    /// Like `min_last_line_words`, but the fewest columns the last line should take up.
+    pub min_last_line_width: usize,
+    /// Whether  Arabic  lines are justified by first elongating words with  kashidas  (U+0640
+    /// ARABIC  TATWEEL),  at most one per word, at the places  Arabic  calligraphy  prefers,
+    /// and only then by widening spaces.
+    pub kashida: bool,
+    /// Used  to find the words of Thai, Lao, Khmer and Burmese text, which  doesn't  put
+    /// spaces  between  them,  so  lines  can  be broken  between  words  there  too  and
+    /// justified normally. `None` breaks lines at spaces only.
+    pub dictionary: Option<&'a Dictionary>,
+    /// The language of the text, whose typographic rules are followed. Whatever the language,
+    /// lines are never broken at no-break spaces.
+    pub language: Language,
+    /// The fewest characters `hyphenate_overflow` leaves before a hyphen.
+    pub left_hyphen_min: usize,
+    /// The fewest characters `hyphenate_overflow` carries over to the next line.
+    pub right_hyphen_min: usize,
+    /// The most lines in a row `hyphenate_overflow` ends with a hyphen; the rest of the word is
+    /// then left whole, even if it is too long. 0 for no limit.
+    pub max_hyphenated_lines: usize,
+    /// Words `hyphenate_overflow` breaks only at the places given, or never, whatever
+    /// `left_hyphen_min`, `right_hyphen_min` and `language` say.
+    pub hyphenation_exceptions: Option<&'a HyphenationExceptions>
 }
 
 impl<'a> Default for Settings<'a> {
@@ -113,10 +286,25 @@ impl<'a> Default for Settings<'a> {
             insert_at: InsertAt::Balanced,
             #[cfg(feature="unicode-width")]
             wcwidth: false,
+            ambiguous_wide: false,
+            direction: Direction::Ltr,
+            visual_order: false,
+            normalization: Normalization::None,
+            preserve_form: false,
             ignore_spaces: false,
             newline: "\n",
             hyphen: "-",
-            separator: "\n\n"
+            separator: "\n\n",
+            single_word: SingleWord::Unpadded,
+            min_last_line_words: 0,
+            min_last_line_width: 0,
+            kashida: false,
+            dictionary: None,
+            language: Language::None,
+            left_hyphen_min: 1,
+            right_hyphen_min: 1,
+            max_hyphenated_lines: 0,
+            hyphenation_exceptions: None
         }
     }
 }
@@ -124,30 +312,48 @@ impl<'a> Default for Settings<'a> {
 /// Generate where we should break and put it into v, like
 /// vec![0, 12, 26, 40, 52, 65]
 fn get_break_indexes(words: &Vec<&str>, settings: &Settings) -> Vec<usize> {
+    let v = break_at_width(words, settings.width, settings);
+    if !is_widow(words, &v, settings) { return v }
+
+    // Break the paragraph again a column narrower at a time, which pushes words down onto the last
+    // line, until it is long enough. Give up once a line before it would be left with one word,
+    // unless it already was, e.g. a word too long to share its line.
+    let lonely = lonely_lines(words, &v);
+    for width in (1..settings.width).rev() {
+        let narrower = break_at_width(words, width, settings);
+        if lonely_lines(words, &narrower).iter().any(|b| !lonely.contains(b)) { break }
+        if !is_widow(words, &narrower, settings) { return narrower }
+    }
+
+    v
+}
+
+/// Where the lines before the last that have a single word start, with breaks `v`.
+fn lonely_lines(words: &[&str], v: &[usize]) -> Vec<usize> {
+    v.windows(2)
+        .filter(|b| words[b[0]..b[1]].iter().filter(|w| !w.is_empty()).count() < 2)
+        .map(|b| b[0])
+        .collect()
+}
+
+/// Where to break `words` so that no line is wider than `width`, if possible.
+fn break_at_width(words: &Vec<&str>, width: usize, settings: &Settings) -> Vec<usize> {
     let mut n = 0;
     let mut v = Vec::with_capacity(words.len()/4);
     v.push(0);
 
     for (i, word) in words.iter().enumerate() {
-        let mut c;
-        #[cfg(feature="unicode-width")] {
-        if settings.wcwidth {
-            c = n + word.width();
-        } else {
-            c = n + word.len();
-        }
-        }
-        #[cfg(not(feature="unicode-width"))] {
-            c = n + word.len();
-        }
+        let c = n + str_width(word, settings);
         if word.len() == 0 { continue }
         // If the last character in the word is whitespace, we have to ignore it in the
         // comparison, otherwise lines which are exactly the right width will be broken
         // as if they were one character too long.
-        let cc = word.chars().nth(word.len()-1);
-        if c - if cc.map_or(false, char::is_whitespace) { 1 } else { 0 } > settings.width {
-            v.push(i);
-            n = word.len();
+        if c - trailing_space_width(word, settings) > width {
+            // A word the language doesn't let end a line goes down with the next.
+            let mut b = i;
+            while b > v[v.len()-1] + 1 && settings.language.binds_to_next(words[b-1]) { b -= 1 }
+            v.push(b);
+            n = words[b..=i].iter().map(|w| str_width(w, settings)).sum();
         } else {
             n = c;
         }
@@ -156,16 +362,42 @@ fn get_break_indexes(words: &Vec<&str>, settings: &Settings) -> Vec<usize> {
     v
 }
 
+/// Width of `words` laid out on one line, not counting trailing whitespace.
+fn line_width(words: &[&str], settings: &Settings) -> usize {
+    let width: usize = words.iter().map(|w| str_width(w, settings)).sum();
+    match words.iter().rev().find(|w| !w.is_empty()) {
+        Some(w) => width - trailing_space_width(w, settings),
+        _ => width
+    }
+}
+
+/// Width of the whitespace `word` ends with, if it does.
+fn trailing_space_width(word: &str, settings: &Settings) -> usize {
+    word.chars().next_back().filter(|c| c.is_whitespace()).map_or(0, |c| char_width(c, settings))
+}
+
+/// Whether the last line of `words` broken at `breaks` falls short of `min_last_line_words` or
+/// `min_last_line_width`, i.e. the paragraph ends in a lone short word.
+fn is_widow(words: &[&str], breaks: &[usize], settings: &Settings) -> bool {
+    if breaks.len() < 2 { return false }
+    let last = &words[breaks[breaks.len()-1]..];
+    last.iter().filter(|w| !w.is_empty()).count() < settings.min_last_line_words
+        || line_width(last, settings) < settings.min_last_line_width
+}
+
 fn lines_from_indexes<'a>(words: &Vec<&'a str>, breaks: &Vec<usize>) -> Vec<Vec<&'a str>> {
     let mut lines: Vec<Vec<&str>> = Vec::with_capacity(breaks.len());
 
     for i in 0..breaks.len()-1 {
         let mut t_v = Vec::from(&words[breaks[i]..breaks[i+1]]);
         let t_l = t_v.len();
-        // Chop the final " " off of the last string in a line
-        // last element of t_v = last element of t_v[0..length of last element of t_v-1]
+        // Chop the final " " off of the last string in a line, if it has one; words found with a
+        // `Dictionary` don't.
         if t_v.len() == 0 { continue }
-        t_v[t_l-1] = &t_v[t_l-1][0..&t_v[t_l-1].len()-1];
+        let last = t_v[t_l-1];
+        if let Some(c) = last.chars().next_back().filter(|c| c.is_whitespace()) {
+            t_v[t_l-1] = &last[..last.len()-c.len_utf8()];
+        }
         lines.push(t_v);
     }
 
@@ -180,12 +412,7 @@ fn spaces_to_add(lines: &Vec<Vec<&str>>, settings: &Settings) -> Vec<usize> {
     let mut spaces: Vec<usize> = Vec::with_capacity(lines.len());
 
     for line in lines.iter() {
-        let mut size = line.iter().fold(0, |acc, &x| acc + x.len());
-        #[cfg(feature="unicode-width")]
-        match settings.wcwidth {
-            true => {size = line.iter().fold(0, |acc, &x| acc + x.width())},
-            false => {}
-        }
+        let size = line.iter().fold(0, |acc, &x| acc + str_width(x, settings));
 
         if settings.width < size {
             spaces.push(0);
@@ -197,47 +424,165 @@ fn spaces_to_add(lines: &Vec<Vec<&str>>, settings: &Settings) -> Vec<usize> {
     spaces
 }
 
-/// Adds the spaces. Should be used with `spaces_to_add`
-fn add_spaces(add: usize, line: &Vec<&str>, insert_at: &InsertAt) -> String {
-    if line.len() == 0 { return String::new() }
-    let v_i = line.len()-1;
-    let mut add_v = vec![0; v_i];
+/// The width of `c` according to Unicode, taking `ambiguous_wide` into account.
+#[cfg(feature="unicode-width")]
+fn unicode_char_width(c: char, settings: &Settings) -> Option<usize> {
+    if settings.ambiguous_wide { c.width_cjk() } else { c.width() }
+}
 
-    if v_i == 0 {
-        return line[0].to_owned()
+/// The width of `s` according to Unicode, taking `ambiguous_wide` into account.
+#[cfg(feature="unicode-width")]
+fn unicode_str_width(s: &str, settings: &Settings) -> usize {
+    if settings.ambiguous_wide { s.width_cjk() } else { s.width() }
+}
+
+/// The number of columns `c` takes up.
+#[cfg(feature="unicode-width")]
+fn char_width(c: char, settings: &Settings) -> usize {
+    if settings.wcwidth {
+        unicode_char_width(c, settings).unwrap_or(0)
+    } else {
+        plain_width(c.encode_utf8(&mut [0; 4]), settings)
     }
+}
 
-    match *insert_at {
-        InsertAt::Left => {
-            for j in (1..v_i+1).into_iter().cycle().take(add) {
-                add_v[j-1] += 1;
-            }
-        },
-        InsertAt::Right => {
-            for j in (1..v_i+1).rev().into_iter().cycle().take(add) {
-                add_v[j-1] += 1;
-            }
-        },
-        InsertAt::Balanced => {
-            for j in (1..v_i+1).into_iter().cycle().take(add) {
-                if j % 2 == 0 { //EVEN
-                    add_v[v_i - (j/2)] += 1;
-                } else { //ODD
-                    add_v[(j/2)] += 1;
-                }
-            }
-        },
-        InsertAt::Custom(f) => {
-            for j in 0..add {
-                add_v[f(j, add, v_i, line)] += 1;
-            }
+#[cfg(not(feature="unicode-width"))]
+fn char_width(c: char, settings: &Settings) -> usize {
+    plain_width(c.encode_utf8(&mut [0; 4]), settings)
+}
+
+/// The number of columns `s` takes up.
+#[cfg(feature="unicode-width")]
+fn str_width(s: &str, settings: &Settings) -> usize {
+    let s = &*measured(s, settings);
+    if settings.wcwidth { unicode_str_width(s, settings) } else { plain_width(s, settings) }
+}
+
+#[cfg(not(feature="unicode-width"))]
+fn str_width(s: &str, settings: &Settings) -> usize {
+    plain_width(&measured(s, settings), settings)
+}
+
+/// The number of columns `s`, as it is measured, takes up without `wcwidth`: its length in bytes,
+/// or, if it is normalized, in characters.
+#[cfg(feature="normalization")]
+fn plain_width(s: &str, settings: &Settings) -> usize {
+    if settings.normalization == Normalization::None { s.len() } else { normalize::columns(s) }
+}
+
+#[cfg(not(feature="normalization"))]
+fn plain_width(s: &str, _settings: &Settings) -> usize {
+    s.len()
+}
+
+/// `s` as it is measured, i.e. normalized as `settings.normalization` asks.
+#[cfg(feature="normalization")]
+fn measured<'a>(s: &'a str, settings: &Settings) -> Cow<'a, str> {
+    normalize::normalize(s, settings)
+}
+
+#[cfg(not(feature="normalization"))]
+fn measured<'a>(s: &'a str, _settings: &Settings) -> Cow<'a, str> {
+    Cow::Borrowed(s)
+}
+
+/// Whether `c` is a pictograph that may be joined to the emoji before it with a ZERO WIDTH JOINER.
+#[cfg(feature="unicode-width")]
+fn is_pictograph(c: char) -> bool {
+    matches!(c, '\u{2300}'..='\u{23FF}' | '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}'
+        | '\u{1F000}'..='\u{1FAFF}')
+}
+
+/// Whether `c` belongs with the character before it, `previous`, as terminals display them:
+/// zero-width (combining) characters, variation selectors and keycaps; skin tone modifiers;
+/// pictographs joined with a ZERO WIDTH JOINER; and the second of a pair of regional indicators,
+/// which make up a flag. `regional` is the number of regional indicators just before `c`.
+#[cfg(feature="unicode-width")]
+fn extends_grapheme(previous: char, c: char, regional: usize) -> bool {
+    c.width() == Some(0)
+        || matches!(c, '\u{1F3FB}'..='\u{1F3FF}')
+        || previous == '\u{200D}' && is_pictograph(c)
+        || matches!(c, '\u{1F1E6}'..='\u{1F1FF}') && regional % 2 == 1
+}
+
+/// Splits `word` into what terminals display as single characters: a character and whatever
+/// `extends_grapheme` attaches to it. Summing `unicode_str_width` over them, rather than over the
+/// characters, counts an emoji sequence as the two columns it is displayed in.
+#[cfg(feature="unicode-width")]
+fn graphemes(word: &str) -> Vec<&str> {
+    let mut ret: Vec<&str> = Vec::with_capacity(word.len());
+    let mut start = 0;
+    let mut previous = '\0';
+    let mut regional = 0;
+
+    for (i, c) in word.char_indices() {
+        if i > 0 && !extends_grapheme(previous, c, regional) {
+            ret.push(&word[start..i]);
+            start = i;
         }
+        regional = if matches!(c, '\u{1F1E6}'..='\u{1F1FF}') { regional + 1 } else { 0 };
+        previous = c;
     }
 
-    let space_s: Vec<String> = add_v.iter()
-        .map(|i|" ".repeat(*i))
-        .collect();
+    if start < word.len() {
+        ret.push(&word[start..]);
+    }
+
+    ret
+}
+
+/// Splits `word` into the units letter-spacing may separate. Without `wcwidth` every character,
+/// with the combining marks after it, is a unit; with it, the `graphemes` of `word` are, so emoji
+/// sequences stay together too. Letters the language keeps together, such as the Dutch "ij", are a
+/// single unit either way.
+#[cfg(feature="unicode-width")]
+fn clusters<'a>(word: &'a str, settings: &Settings) -> Vec<&'a str> {
+    let units = if settings.wcwidth { graphemes(word) } else { chars(word) };
+    settings.language.join(word, units)
+}
+
+#[cfg(not(feature="unicode-width"))]
+fn clusters<'a>(word: &'a str, settings: &Settings) -> Vec<&'a str> {
+    settings.language.join(word, chars(word))
+}
+
+/// Splits `word` into its characters, each with the combining marks that follow it.
+fn chars(word: &str) -> Vec<&str> {
+    let mut ret: Vec<&str> = Vec::with_capacity(word.len());
+    let mut start = 0;
+
+    for (i, c) in word.char_indices().skip(1) {
+        if is_combining(c) { continue }
+        ret.push(&word[start..i]);
+        start = i;
+    }
+
+    if start < word.len() {
+        ret.push(&word[start..]);
+    }
 
+    ret
+}
+
+/// Whether `c` is a combining mark, which belongs to the character before it.
+#[cfg(feature="normalization")]
+fn is_combining(c: char) -> bool {
+    unicode_normalization::char::is_combining_mark(c)
+}
+
+/// Whether `c` is a combining mark, which belongs to the character before it. Without the
+/// `normalization` feature's tables, only the blocks of combining diacritics and the Hebrew and
+/// Arabic marks are known.
+#[cfg(not(feature="normalization"))]
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36F}' | '\u{483}'..='\u{489}' | '\u{591}'..='\u{5BD}' | '\u{5BF}'
+        | '\u{5C1}' | '\u{5C2}' | '\u{5C4}' | '\u{5C5}' | '\u{5C7}' | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}' | '\u{20D0}'..='\u{20FF}' | '\u{FE20}'..='\u{FE2F}')
+        || kashida::is_mark(c)
+}
+
+/// Joins `line`, putting `add_v[i]` spaces after the i-th word.
+fn join_with_spaces(line: &[&str], add_v: &[usize]) -> String {
     // Length of spaces
     let space_l: usize = add_v.iter().sum();
     // Length of text in line
@@ -249,7 +594,7 @@ fn add_spaces(add: usize, line: &Vec<&str>, insert_at: &InsertAt) -> String {
             String::with_capacity(space_l + line_l),
             |acc, (i, x)| {
                 if i < line.len()-1 {
-                    acc + x + &space_s[i]
+                    acc + x + &" ".repeat(add_v[i])
                 } else {
                     acc + x
                 }
@@ -257,6 +602,51 @@ fn add_spaces(add: usize, line: &Vec<&str>, insert_at: &InsertAt) -> String {
         )
 }
 
+/// Adds the spaces, and kashidas with `settings.kashida`. Should be used with `spaces_to_add`.
+/// Also returns how many spaces went into each gap, which is passed on to the distributor of the
+/// next line.
+fn add_spaces(line: &Line, settings: &Settings) -> (String, Vec<usize>) {
+    if settings.kashida && line.spaces > 0 {
+        let (words, spaces) = kashida::elongate(line.words, line.spaces, settings);
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        let widths: Vec<usize> = words.iter().map(|w| str_width(w, settings)).collect();
+        return widen_spaces(&Line { words: &words, widths: &widths, spaces, ..*line }, settings);
+    }
+    widen_spaces(line, settings)
+}
+
+/// `add_spaces`, once kashidas have been added.
+fn widen_spaces(line: &Line, settings: &Settings) -> (String, Vec<usize>) {
+    if line.words.is_empty() { return (String::new(), Vec::new()) }
+    let word = line.words[0];
+
+    if line.gaps() == 0 {
+        return match settings.single_word {
+            SingleWord::Unpadded => (word.to_owned(), Vec::new()),
+            SingleWord::LetterSpacing => {
+                let units = clusters(word, settings);
+                if units.len() < 2 { return (word.to_owned(), Vec::new()) }
+                let widths: Vec<usize> = units.iter().map(|u| str_width(u, settings)).collect();
+                let add_v = distribute::distribute(&Line { words: &units, widths: &widths, ..*line }, &settings.insert_at);
+                (join_with_spaces(&units, &add_v), add_v)
+            },
+            SingleWord::Fill(c) => {
+                let mut ret = word.to_owned();
+                ret.extend(std::iter::repeat_n(c, line.spaces));
+                (ret, Vec::new())
+            }
+        }
+    }
+
+    let add_v = distribute::distribute(line, &settings.insert_at);
+    (join_with_spaces(line.words, &add_v), add_v)
+}
+
+/// Whether a line may be broken at `c`: whitespace, except for no-break spaces.
+fn is_break(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, '\u{A0}' | '\u{2007}' | '\u{202F}')
+}
+
 /// This function is needed because there is no better way(?) to split a string such that the sum
 /// of the lengths of the output equals the length of the input.  That is to say: "e
 /// e".split(char::is_whitespace) returns vec!["e", "e"] while we want vec!["e ", "e"]
@@ -265,8 +655,8 @@ fn split_into_words(text: &str) -> Vec<&str> {
 
     let indices: Vec<_> = zero.into_iter()
         .chain(
-            text.match_indices(char::is_whitespace)
-            .map(|(i, _)|i+1)
+            text.match_indices(is_break)
+            .map(|(i, s)|i+s.len())
             )
         .collect();
 
@@ -284,126 +674,69 @@ fn split_into_words(text: &str) -> Vec<&str> {
     wwords
 }
 
-#[cfg(feature="unicode-width")]
 fn hyphenate_overflow(text: &str, settings: &Settings) -> String {
-    let mut ret = String::with_capacity(text.len());
     let sws: Vec<_>;
     let joiner: &str;
     if settings.ignore_spaces {
         sws = text.split(settings.newline).collect();
         joiner = settings.newline;
     } else {
-        sws = text.split_whitespace().collect();
+        sws = text.split(is_break).filter(|s| !s.is_empty()).collect();
         joiner = " ";
     }
-    let tl = sws.len();
-
-    for (i, s) in sws.iter().enumerate() {
-        if s.len() > settings.width {
-            let h = s.chars()
-                .collect::<Vec<_>>();
-
-            let widths: Vec<usize> = h.iter()
-                .map(|e| e.width().unwrap_or(0))
-                .collect();
-
-            let mut q = 0;
-            let mut hq = vec![0];
-            for (i, w) in widths.into_iter().enumerate() {
-                q += w;
-                if q > settings.width-(settings.hyphen.len()) {
-                    hq.push(i);
-                    q=w;
-                }
-            }
 
-            let mut hhq = Vec::new();
-            for e in hq.windows(2) {
-                if e.len() == 2 {
-                    hhq.push(&h[e[0]..e[1]]);
-                } else {
-                    continue
-                }
-            }
-            hhq.push(&h[*hq.last().unwrap()..]);
-
-            let mut hh = hhq.iter().peekable();
-
-            let mut f: Vec<String> = Vec::new();
-            loop {
-                let s: String = hh.next().unwrap().iter().collect();
-                if hh.peek().is_some() {
-                    f.push(s + settings.hyphen);
-                } else {
-                    f.push(s);
-                    break
-                }
-            }
-
-            ret += &f.join(joiner);
-        } else {
-            ret += s;
-        }
-        if i != tl-1 {
-            ret += joiner;
-        }
-    }
-
-    ret
+    sws.iter()
+        .map(|s| hyphenate_word(s, settings).join(joiner))
+        .collect::<Vec<_>>()
+        .join(joiner)
 }
 
-#[cfg(not(feature="unicode-width"))]
-fn hyphenate_overflow(text: &str, settings: &Settings) -> String {
-    let mut ret = String::with_capacity(text.len());
-    let sws: Vec<_>;
-    let joiner: &str;
-    if settings.ignore_spaces {
-        sws = text.split(settings.newline).collect();
-        joiner = settings.newline;
-    } else {
-        sws = text.split_whitespace().collect();
-        joiner = " ";
+/// The pieces `hyphenate_overflow` breaks `word` into, all but the last ending in
+/// `settings.hyphen`. A word that fits on a line is left whole.
+fn hyphenate_word(word: &str, settings: &Settings) -> Vec<String> {
+    if str_width(word, settings) <= settings.width {
+        return vec![word.to_owned()]
     }
-    let tl = sws.len();
-
-    for (i, s) in sws.iter().enumerate() {
-        if s.len() > settings.width {
-            let h = s.chars().collect::<Vec<_>>();
-
-            let mut f: Vec<String> = Vec::new();
-            let mut p = h.chunks(settings.width-(settings.hyphen.len())).peekable();
 
-            loop {
-                let s: String = p.next().unwrap().iter().collect();
-                if p.peek().is_some() {
-                    f.push(s + settings.hyphen);
-                } else {
-                    f.push(s);
-                    break
-                }
-            }
+    #[cfg(feature="unicode-width")]
+    let h = graphemes(word);
+    #[cfg(not(feature="unicode-width"))]
+    let h = chars(word);
 
-            ret += &f.join(joiner);
-        } else {
-            ret += s;
-        }
-        if i != tl-1 {
-            ret += joiner;
-        }
-    }
+    let widths: Vec<usize> = h.iter().map(|e| str_width(e, settings)).collect();
+    let mut hq = hyphenation::points(&h, &widths, settings.width-(settings.hyphen.len()), settings);
+    hq.push(h.len());
 
-    ret
+    let last = hq.len() - 2;
+    hq.windows(2)
+        .enumerate()
+        .map(|(i, e)| {
+            let s = h[e[0]..e[1]].concat();
+            if i < last { s + settings.hyphen } else { s }
+        })
+        .collect()
 }
 
 /// Justify a single paragraph. Panics if "paragraph" contains newlines.
 pub fn justify_paragraph(text: &str, settings: &Settings) -> String {
+    #[cfg(feature="normalization")]
+    let text = &*normalize::input(text, settings);
+    justify_nth_paragraph(text, settings, 0)
+}
+
+/// `justify_paragraph` for the `paragraph`-th (0-indexed) paragraph of a text.
+fn justify_nth_paragraph(text: &str, settings: &Settings, paragraph: usize) -> String {
     if text.contains("\n") {
         panic!("Expected `text` to contain no newlines but it did")
     }
 
     let mut ret = String::with_capacity(text.len() + (text.len() / 3));
 
-    let words = split_into_words(text);
+    let prepared = settings.language.prepare(text);
+    let mut words = split_into_words(&prepared);
+    if let Some(dictionary) = settings.dictionary {
+        words = segment::split_words(&words, dictionary);
+    }
     //eprintln!("W:{:?}",words);
     let breaks = get_break_indexes(&words, &settings);
     //eprintln!("B:{:?}",breaks);
@@ -411,6 +744,8 @@ pub fn justify_paragraph(text: &str, settings: &Settings) -> String {
     //eprintln!("L:{:?}",lines);
     let spaces = spaces_to_add(&lines, &settings);
     //eprintln!("S:{:?}",spaces);
+    let mut previous: Option<Vec<usize>> = None;
+    let mut previous_columns: Vec<usize> = Vec::new();
 
     for (i, space) in spaces.iter().enumerate() {
         if !settings.justify_last_line && i == spaces.len() - 1 {
@@ -418,19 +753,36 @@ pub fn justify_paragraph(text: &str, settings: &Settings) -> String {
             break
         }
         if !settings.ignore_spaces {
-            let add = &add_spaces(*space, &lines[i], &settings.insert_at);
-            ret += add;
+            let widths: Vec<usize> = lines[i].iter().map(|w| str_width(w, settings)).collect();
+            let line = Line {
+                words: &lines[i],
+                widths: &widths,
+                spaces: *space,
+                number: i,
+                paragraph,
+                previous: previous.as_deref(),
+                previous_columns: &previous_columns
+            };
+            let (add, add_v) = add_spaces(&line, settings);
+            previous_columns = rivers::gap_columns(&add, settings);
+            ret += &add;
+            previous = Some(add_v);
         } else {
             ret += &lines[i].join(" ");
         }
         ret += settings.newline;
     }
 
+    #[cfg(feature="bidi")]
+    let ret = bidi::lay_out(text, ret, settings);
+
     ret
 }
 
 /// Justify `text` according to the parameters in `settings`.
 pub fn justify(text: &str, settings: &Settings) -> String {
+    #[cfg(feature="normalization")]
+    let text = &*normalize::input(text, settings);
     let mut h = String::new();
     if settings.hyphenate_overflow {
         h = hyphenate_overflow(text, &settings);
@@ -445,8 +797,9 @@ pub fn justify(text: &str, settings: &Settings) -> String {
         .filter(
             |e|e.len()!=0
             )
+        .enumerate()
         .map(
-            |p| justify_paragraph(p, settings)
+            |(i, p)| justify_nth_paragraph(p, settings, i)
             )
         .collect::<Vec<_>>()
         .join(settings.separator)
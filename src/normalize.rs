@@ -0,0 +1,31 @@
+//! Unicode normalization of the text being justified.
+
+use std::borrow::Cow;
+
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick, is_nfkc_quick};
+use unicode_normalization::char::is_combining_mark;
+
+use {Normalization, Settings};
+
+/// `s` in the form `settings.normalization` asks for. Text that is already in it is borrowed.
+pub(crate) fn normalize<'a>(s: &'a str, settings: &Settings) -> Cow<'a, str> {
+    match settings.normalization {
+        Normalization::None => Cow::Borrowed(s),
+        Normalization::Nfc if is_nfc_quick(s.chars()) == IsNormalized::Yes => Cow::Borrowed(s),
+        Normalization::Nfc => Cow::Owned(s.nfc().collect()),
+        Normalization::Nfkc if is_nfkc_quick(s.chars()) == IsNormalized::Yes => Cow::Borrowed(s),
+        Normalization::Nfkc => Cow::Owned(s.nfkc().collect())
+    }
+}
+
+/// The text `justify` lays out: `text` normalized, unless `settings.preserve_form`, in which case
+/// it is only normalized to be measured.
+pub(crate) fn input<'a>(text: &'a str, settings: &Settings) -> Cow<'a, str> {
+    if settings.preserve_form { Cow::Borrowed(text) } else { normalize(text, settings) }
+}
+
+/// The number of columns `s`, already normalized, takes up without `wcwidth`: one for each
+/// character, combining marks taking none.
+pub(crate) fn columns(s: &str) -> usize {
+    s.chars().filter(|&c| !is_combining_mark(c)).count()
+}
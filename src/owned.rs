@@ -0,0 +1,161 @@
+//! Settings that own their data.
+
+use {Direction, InsertAt, Language, Normalization, Settings, SingleWord};
+
+/// The  owned  counterpart  of `InsertAt`, without  the  variants  that  borrow  a
+/// function or distributor (use with `SettingsBuf`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(rename_all="snake_case"))]
+pub enum InsertAtBuf {
+    /// See `InsertAt::Left`.
+    Left,
+    /// See `InsertAt::Right`.
+    Right,
+    /// See `InsertAt::Balanced`.
+    #[default]
+    Balanced,
+    /// See `InsertAt::Alternating`.
+    Alternating,
+    /// See `InsertAt::AvoidRivers`.
+    AvoidRivers,
+    /// See `InsertAt::StatelessRandom`.
+    StatelessRandom(u64)
+}
+
+impl<'a> From<InsertAtBuf> for InsertAt<'a> {
+    fn from(insert_at: InsertAtBuf) -> Self {
+        match insert_at {
+            InsertAtBuf::Left => InsertAt::Left,
+            InsertAtBuf::Right => InsertAt::Right,
+            InsertAtBuf::Balanced => InsertAt::Balanced,
+            InsertAtBuf::Alternating => InsertAt::Alternating,
+            InsertAtBuf::AvoidRivers => InsertAt::AvoidRivers,
+            InsertAtBuf::StatelessRandom(seed) => InsertAt::StatelessRandom(seed)
+        }
+    }
+}
+
+/// An owned version of `Settings`, which can be kept in long-lived structures, sent between
+/// threads and, with the `serde` feature, serialized and deserialized. Missing fields take
+/// their default values when deserializing. Borrow it as `Settings` with `settings()` or
+/// `Settings::from`; `Settings::dictionary` and `Settings::hyphenation_exceptions` are then
+/// `None`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature="serde", serde(default))]
+pub struct SettingsBuf {
+    /// See `Settings::justify_last_line`.
+    pub justify_last_line: bool,
+    /// See `Settings::hyphenate_overflow`.
+    pub hyphenate_overflow: bool,
+    /// See `Settings::width`.
+    pub width: usize,
+    /// See `Settings::insert_at`.
+    pub insert_at: InsertAtBuf,
+    /// See `Settings::wcwidth`. Ignored unless the `unicode-width` feature is enabled.
+    pub wcwidth: bool,
+    /// See `Settings::ambiguous_wide`.
+    pub ambiguous_wide: bool,
+    /// See `Settings::direction`.
+    pub direction: Direction,
+    /// See `Settings::visual_order`.
+    pub visual_order: bool,
+    /// See `Settings::normalization`.
+    pub normalization: Normalization,
+    /// See `Settings::preserve_form`.
+    pub preserve_form: bool,
+    /// See `Settings::ignore_spaces`.
+    pub ignore_spaces: bool,
+    /// See `Settings::newline`.
+    pub newline: String,
+    /// See `Settings::hyphen`.
+    pub hyphen: String,
+    /// See `Settings::separator`.
+    pub separator: String,
+    /// See `Settings::single_word`.
+    pub single_word: SingleWord,
+    /// See `Settings::min_last_line_words`.
+    pub min_last_line_words: usize,
+    /// See `Settings::min_last_line_width`.
+    pub min_last_line_width: usize,
+    /// See `Settings::kashida`.
+    pub kashida: bool,
+    /// See `Settings::language`.
+    pub language: Language,
+    /// See `Settings::left_hyphen_min`.
+    pub left_hyphen_min: usize,
+    /// See `Settings::right_hyphen_min`.
+    pub right_hyphen_min: usize,
+    /// See `Settings::max_hyphenated_lines`.
+    pub max_hyphenated_lines: usize
+}
+
+impl Default for SettingsBuf {
+    fn default() -> Self {
+        let settings = Settings::default();
+        SettingsBuf {
+            justify_last_line: settings.justify_last_line,
+            hyphenate_overflow: settings.hyphenate_overflow,
+            width: settings.width,
+            insert_at: InsertAtBuf::default(),
+            wcwidth: false,
+            ambiguous_wide: false,
+            direction: Direction::Ltr,
+            visual_order: false,
+            normalization: Normalization::None,
+            preserve_form: false,
+            ignore_spaces: settings.ignore_spaces,
+            newline: settings.newline.to_owned(),
+            hyphen: settings.hyphen.to_owned(),
+            separator: settings.separator.to_owned(),
+            single_word: settings.single_word,
+            min_last_line_words: settings.min_last_line_words,
+            min_last_line_width: settings.min_last_line_width,
+            kashida: settings.kashida,
+            language: settings.language,
+            left_hyphen_min: settings.left_hyphen_min,
+            right_hyphen_min: settings.right_hyphen_min,
+            max_hyphenated_lines: settings.max_hyphenated_lines
+        }
+    }
+}
+
+impl SettingsBuf {
+    /// Borrows these settings as `Settings`, for `justify` and friends.
+    pub fn settings(&self) -> Settings<'_> {
+        Settings::from(self)
+    }
+}
+
+impl<'a> From<&'a SettingsBuf> for Settings<'a> {
+    fn from(buf: &'a SettingsBuf) -> Self {
+        Settings {
+            justify_last_line: buf.justify_last_line,
+            hyphenate_overflow: buf.hyphenate_overflow,
+            width: buf.width,
+            insert_at: buf.insert_at.into(),
+            #[cfg(feature="unicode-width")]
+            wcwidth: buf.wcwidth,
+            ambiguous_wide: buf.ambiguous_wide,
+            direction: buf.direction,
+            visual_order: buf.visual_order,
+            normalization: buf.normalization,
+            preserve_form: buf.preserve_form,
+            ignore_spaces: buf.ignore_spaces,
+            newline: &buf.newline,
+            hyphen: &buf.hyphen,
+            separator: &buf.separator,
+            single_word: buf.single_word,
+            min_last_line_words: buf.min_last_line_words,
+            min_last_line_width: buf.min_last_line_width,
+            kashida: buf.kashida,
+            dictionary: None,
+            language: buf.language,
+            left_hyphen_min: buf.left_hyphen_min,
+            right_hyphen_min: buf.right_hyphen_min,
+            max_hyphenated_lines: buf.max_hyphenated_lines,
+            hyphenation_exceptions: None
+        }
+    }
+}
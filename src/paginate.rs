@@ -0,0 +1,121 @@
+//! Splitting justified text into pages.
+
+use {Settings, str_width};
+
+/// How `paginate` lays out pages
+pub struct Pagination<'a> {
+    /// The number of lines on a page, including the header and footer.
+    pub length: usize,
+    /// Printed  at the top of every page, each of its lines centered in  `Settings::width`.
+    /// `{title}`  is  replaced  by  `title`, `{page}` by the 1-indexed  page  number  and
+    /// `{pages}` by the number of pages. Empty for no header.
+    pub header: &'a str,
+    /// Like `header`, but printed at the bottom of every page.
+    pub footer: &'a str,
+    /// What `{title}` stands for in `header` and `footer`.
+    pub title: &'a str,
+    /// Whether pages are separated by a form feed. If not, every page is padded with blank lines
+    /// to `length`; if so, only pages with a footer are, to keep it at the bottom.
+    pub form_feed: bool,
+    /// The fewest lines of a paragraph that may be carried over to the top of a page.
+    pub widows: usize,
+    /// The fewest lines of a paragraph that may be left at the bottom of a page.
+    pub orphans: usize
+}
+
+impl<'a> Default for Pagination<'a> {
+    fn default() -> Self {
+        Pagination {
+            length: 66,
+            header: "",
+            footer: "",
+            title: "",
+            form_feed: false,
+            widows: 2,
+            orphans: 2
+        }
+    }
+}
+
+/// Moves  `end`, the end of a page that would break the paragraph it falls in, so as to
+/// leave  at  least `widows` lines of the paragraph on the next page and  `orphans`  on
+/// this  one,  or  the  whole paragraph on the next page. A  paragraph  that  can't  be
+/// moved without leaving the page starting at `start` empty is broken where it is.
+fn avoid_widows_and_orphans(lines: &[&str], start: usize, end: usize, pagination: &Pagination) -> usize {
+    let blank = |i: usize| lines[i].trim().is_empty();
+    if end >= lines.len() || blank(end - 1) || blank(end) {
+        return end
+    }
+    let paragraph_start = (0..end).rev().find(|&i| blank(i)).map_or(0, |i| i + 1);
+    let paragraph_end = (end..lines.len()).find(|&i| blank(i)).unwrap_or(lines.len());
+
+    let mut k = end;
+    if paragraph_end - k < pagination.widows {
+        k = paragraph_end.saturating_sub(pagination.widows);
+    }
+    if k < paragraph_start + pagination.orphans {
+        k = paragraph_start;
+    }
+    if k > start { k } else { end }
+}
+
+/// Fills in `template` and centers each of its lines.
+fn render(template: &str, page: usize, pages: usize, settings: &Settings, pagination: &Pagination) -> Vec<String> {
+    if template.is_empty() {
+        return Vec::new()
+    }
+    let filled = template
+        .replace("{title}", pagination.title)
+        .replace("{pages}", &pages.to_string())
+        .replace("{page}", &page.to_string());
+
+    filled.split(settings.newline)
+        .map(|line| {
+            let pad = settings.width.saturating_sub(str_width(line, settings)) / 2;
+            if line.is_empty() { String::new() } else { " ".repeat(pad) + line }
+        })
+        .collect()
+}
+
+/// Splits  already  justified  `text`,  e.g. from `justify` or  `justify_columns`,  into
+/// pages  as described by `pagination`. Paragraphs are runs of lines that  aren't  blank;
+/// blank lines are dropped from the top of a page. The lines are separated by
+/// `settings.newline`, and headers and footers are centered in `settings.width`.
+pub fn paginate(text: &str, settings: &Settings, pagination: &Pagination) -> String {
+    let text = text.strip_suffix(settings.newline).unwrap_or(text);
+    let lines: Vec<&str> = text.split(settings.newline).collect();
+    let margins = render(pagination.header, 0, 0, settings, pagination).len()
+        + render(pagination.footer, 0, 0, settings, pagination).len();
+    let body = pagination.length.saturating_sub(margins).max(1);
+
+    let mut breaks = Vec::new();
+    let mut start = 0;
+    loop {
+        while start < lines.len() && lines[start].trim().is_empty() {
+            start += 1;
+        }
+        if start >= lines.len() { break }
+        let end = avoid_widows_and_orphans(&lines, start, (start + body).min(lines.len()), pagination);
+        breaks.push((start, end));
+        start = end;
+    }
+
+    let pad = !pagination.form_feed || !pagination.footer.is_empty();
+    let pages: Vec<String> = breaks.iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let mut page = render(pagination.header, i + 1, breaks.len(), settings, pagination);
+            // The blank line between paragraphs isn't needed at the bottom of a page.
+            let end = (start..end).rev().find(|&i| !lines[i].trim().is_empty()).map_or(start, |i| i + 1);
+            page.extend(lines[start..end].iter().map(|l| l.to_string()));
+            if pad {
+                page.resize(page.len() + body - (end - start), String::new());
+            }
+            page.extend(render(pagination.footer, i + 1, breaks.len(), settings, pagination));
+            page.join(settings.newline)
+        })
+        .collect();
+
+    let separator = if pagination.form_feed { format!("{}\x0C", settings.newline) } else { settings.newline.to_owned() };
+    pages.join(&separator)
+}
@@ -0,0 +1,76 @@
+//! Finding "rivers", i.e. gaps between words that line up vertically over several lines.
+
+use {Settings, clusters, str_width};
+
+/// A vertical run of spaces between words in justified text, as found by `find_rivers`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct River {
+    /// The 0-indexed column the river runs down.
+    pub column: usize,
+    /// The 0-indexed line the river starts on.
+    pub line: usize,
+    /// The number of consecutive lines the river spans.
+    pub length: usize
+}
+
+/// The 0-indexed columns of the spaces in `line` that fall between two words, i.e. not counting
+/// leading or trailing whitespace. The result is sorted.
+pub(crate) fn gap_columns(line: &str, settings: &Settings) -> Vec<usize> {
+    let mut ret = Vec::new();
+    let mut pending = Vec::new();
+    let mut seen_word = false;
+    let mut column = 0;
+
+    for c in clusters(line, settings) {
+        if c.starts_with(char::is_whitespace) {
+            if seen_word { pending.push(column) }
+        } else {
+            seen_word = true;
+            ret.append(&mut pending);
+        }
+        column += str_width(c, settings);
+    }
+
+    ret
+}
+
+/// Finds the rivers in `text`, which should already be justified: every column in which at least
+/// `min_length` consecutive lines have a space between two words. Lines are split on
+/// `settings.newline`, and columns are counted as `justify` counts them. Empty lines (e.g. between
+/// paragraphs) interrupt rivers, and a river always spans at least two lines. Rivers are ordered
+/// by starting line, then by column.
+pub fn find_rivers(text: &str, settings: &Settings, min_length: usize) -> Vec<River> {
+    let mut rivers = Vec::new();
+    // (column, starting line) of the rivers that reached the previous line
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let lines: Vec<&str> = text.split(settings.newline).collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let columns = gap_columns(line, settings);
+        let mut next = Vec::with_capacity(columns.len());
+
+        for (column, start) in open.drain(..) {
+            if columns.binary_search(&column).is_ok() {
+                next.push((column, start));
+            } else if i - start >= min_length.max(2) {
+                rivers.push(River { column, line: start, length: i - start });
+            }
+        }
+        for column in columns {
+            if next.iter().all(|&(c, _)| c != column) {
+                next.push((column, i));
+            }
+        }
+
+        open = next;
+    }
+
+    for (column, start) in open {
+        if lines.len() - start >= min_length.max(2) {
+            rivers.push(River { column, line: start, length: lines.len() - start });
+        }
+    }
+
+    rivers.sort_by_key(|r| (r.line, r.column));
+    rivers
+}
@@ -0,0 +1,153 @@
+//! Finding the words of Thai, Lao, Khmer and Burmese text, which doesn't put spaces between them.
+
+use std::collections::HashSet;
+
+/// A list of words, used to find where lines may be broken in text that doesn't separate its words
+/// with spaces (use with `Settings::dictionary`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// The length of the longest word, in characters.
+    longest: usize
+}
+
+impl Dictionary {
+    /// A dictionary of `words`.
+    pub fn new<I: IntoIterator<Item=S>, S: Into<String>>(words: I) -> Self {
+        let mut dictionary = Dictionary::default();
+        for word in words {
+            dictionary.insert(word);
+        }
+        dictionary
+    }
+
+    /// A dictionary of the words in `list`, one per line. Blank lines and lines starting with `#`
+    /// are skipped.
+    pub fn from_list(list: &str) -> Self {
+        Dictionary::new(list.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#')))
+    }
+
+    /// A small dictionary of common Thai words, embedded in the crate.
+    #[cfg(feature="thai-dictionary")]
+    pub fn thai() -> Self {
+        Dictionary::from_list(include_str!("dictionaries/thai.txt"))
+    }
+
+    /// Adds `word` to the dictionary.
+    pub fn insert<S: Into<String>>(&mut self, word: S) {
+        let word = word.into();
+        self.longest = self.longest.max(word.chars().count());
+        self.words.insert(word);
+    }
+
+    /// Whether `word` is in the dictionary.
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+}
+
+/// Whether `c` is written in one of the scripts words are found in with a `Dictionary`.
+fn is_unspaced(c: char) -> bool {
+    matches!(c, '\u{E00}'..='\u{EFF}' | '\u{1000}'..='\u{109F}' | '\u{1780}'..='\u{17FF}')
+}
+
+/// Whether a word may start with `c`, i.e. it isn't a vowel sign, tone mark or the like that
+/// belongs to the letter before it.
+fn can_start(c: char) -> bool {
+    !matches!(c,
+        // Thai
+        '\u{E2F}' | '\u{E30}'..='\u{E3A}' | '\u{E45}'..='\u{E4E}'
+        // Lao
+        | '\u{EB0}'..='\u{EBC}' | '\u{EC6}' | '\u{EC8}'..='\u{ECD}'
+        // Burmese
+        | '\u{102B}'..='\u{103E}' | '\u{1056}'..='\u{1059}' | '\u{105E}'..='\u{1060}'
+        | '\u{1062}'..='\u{1064}' | '\u{1067}'..='\u{106D}' | '\u{1071}'..='\u{1074}'
+        | '\u{1082}'..='\u{108D}' | '\u{108F}' | '\u{109A}'..='\u{109D}'
+        // Khmer
+        | '\u{17B4}'..='\u{17D3}' | '\u{17DD}')
+}
+
+/// Whether a word may end with `c`, i.e. it isn't a vowel written before the consonant it
+/// follows, or a sign that stacks the next consonant under it.
+fn can_end(c: char) -> bool {
+    !matches!(c, '\u{E40}'..='\u{E44}' | '\u{EC0}'..='\u{EC4}' | '\u{1039}' | '\u{17D2}')
+}
+
+/// Splits `run`, which is entirely in the scripts of `is_unspaced`, into as few pieces as
+/// possible that aren't in `dictionary`, and then into as few pieces as possible. Consecutive
+/// unknown pieces are kept together. Returns the byte indices the pieces start at, after the first.
+fn segment_run(run: &str, dictionary: &Dictionary) -> Vec<usize> {
+    let chars: Vec<(usize, char)> = run.char_indices().collect();
+    // The (byte, character) indices pieces may start or end at.
+    let mut bounds = vec![(0, 0)];
+    for k in 1..chars.len() {
+        if can_start(chars[k].1) && can_end(chars[k-1].1) {
+            bounds.push((chars[k].0, k));
+        }
+    }
+    bounds.push((run.len(), chars.len()));
+
+    // best[k] is the (unknown pieces, pieces, previous bound, known) of the best way to get to
+    // bounds[k].
+    let mut best: Vec<Option<(usize, usize, usize, bool)>> = vec![None; bounds.len()];
+    best[0] = Some((0, 0, 0, true));
+    for k in 0..bounds.len()-1 {
+        let (unknown, pieces, _, _) = match best[k] { Some(b) => b, None => continue };
+        for e in k+1..bounds.len() {
+            if e > k + 1 && bounds[e].1 - bounds[k].1 > dictionary.longest { break }
+            // A word may be followed by a repetition or abbreviation mark.
+            let piece = run[bounds[k].0..bounds[e].0].trim_end_matches(['\u{E2F}', '\u{E46}']);
+            let known = dictionary.contains(piece);
+            // Unknown pieces are only ever one bound long.
+            if !known && e > k + 1 { continue }
+            let cost = (unknown + if known { 0 } else { 1 }, pieces + 1, k, known);
+            if best[e].is_none_or(|b| (cost.0, cost.1) < (b.0, b.1)) {
+                best[e] = Some(cost);
+            }
+        }
+    }
+
+    let mut starts = Vec::new();
+    let mut k = bounds.len() - 1;
+    let mut following_known = true;
+    while k > 0 {
+        let (_, _, previous, known) = best[k].unwrap();
+        if k != bounds.len() - 1 && (known || following_known) {
+            starts.push(bounds[k].0);
+        }
+        following_known = known;
+        k = previous;
+    }
+    starts.reverse();
+    starts
+}
+
+/// Splits the words of `words`, as from `split_into_words`, further wherever `dictionary` finds
+/// a word boundary inside a run of Thai, Lao, Khmer or Burmese text.
+pub(crate) fn split_words<'a>(words: &[&'a str], dictionary: &Dictionary) -> Vec<&'a str> {
+    let mut ret = Vec::with_capacity(words.len());
+
+    for word in words {
+        let mut start = 0;
+        let mut run: Option<usize> = None;
+        // Each character, and a sentinel at the end to close the last run.
+        for (i, c) in word.char_indices().map(|(i, c)| (i, Some(c))).chain(Some((word.len(), None))) {
+            match (run, c.is_some_and(is_unspaced)) {
+                (None, true) => run = Some(i),
+                (Some(r), false) => {
+                    for s in segment_run(&word[r..i], dictionary) {
+                        ret.push(&word[start..r+s]);
+                        start = r + s;
+                    }
+                    run = None;
+                },
+                _ => {}
+            }
+        }
+        ret.push(&word[start..]);
+    }
+
+    ret
+}
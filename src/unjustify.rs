@@ -0,0 +1,78 @@
+//! Undoing justification.
+
+use {Settings, hyphenate_word, is_break};
+use check::paragraphs;
+
+/// How many of `lines`, from the first, are pieces of a word `hyphenate_overflow` broke up, the
+/// rest of the word being the first word of the line after them; 0 if the first line isn't one.
+/// The word is broken again as `hyphenate_overflow` would, so a word that genuinely ends in a
+/// hyphen, or one broken where `settings` wouldn't, isn't mistaken for one.
+fn hyphenated_lines(lines: &[&str], settings: &Settings) -> usize {
+    let hyphen = settings.hyphen;
+    if !settings.hyphenate_overflow || hyphen.is_empty() {
+        return 0
+    }
+    let fragment = |line: &str| line.trim().strip_suffix(hyphen)
+        .filter(|f| !f.is_empty() && !f.contains(is_break))
+        .map(str::to_owned);
+    let fragments: Vec<String> = lines.iter().map_while(|l| fragment(l)).collect();
+
+    (1..=fragments.len().min(lines.len() - 1)).rev()
+        .find(|&k| {
+            let rest = match lines[k].split(is_break).find(|w| !w.is_empty()) {
+                Some(rest) => rest,
+                None => return false
+            };
+            let pieces = hyphenate_word(&(fragments[..k].concat() + rest), settings);
+            pieces.len() == k + 1
+                && pieces[..k].iter().zip(lines).all(|(p, l)| p == l.trim())
+                && pieces[k] == rest
+        })
+        .unwrap_or(0)
+}
+
+/// Joins the lines of one justified paragraph back into a single line, removing padding and the
+/// hyphens `hyphenate_overflow` inserted. No-break spaces, like the thin spaces French puts before
+/// punctuation, are kept.
+pub(crate) fn join_lines(lines: &[&str], settings: &Settings) -> String {
+    let mut ret = String::new();
+    let mut glue = "";
+    // The lines before this one end in a hyphen `hyphenate_overflow` added.
+    let mut hyphenated = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        ret += glue;
+        if settings.ignore_spaces {
+            ret += line;
+            continue
+        }
+        let words: Vec<&str> = line.split(is_break).filter(|w| !w.is_empty()).collect();
+        ret += &words.join(" ");
+        glue = " ";
+        if i >= hyphenated {
+            hyphenated = i + hyphenated_lines(&lines[i..], settings);
+        }
+        if i < hyphenated {
+            ret.truncate(ret.len() - settings.hyphen.len());
+            glue = "";
+        }
+    }
+
+    ret
+}
+
+/// The inverse of `justify`: removes the padding between words, joins the lines of each paragraph
+/// back into one and removes the hyphens added by `hyphenate_overflow` (if it is on in
+/// `settings`). Paragraphs are taken to be separated by `settings.separator` or by blank lines,
+/// and are joined with `settings.newline`, so the result can be passed to `justify` again, e.g.
+/// with a different width.
+pub fn unjustify(text: &str, settings: &Settings) -> String {
+    let separator = if settings.separator.is_empty() { settings.newline } else { settings.separator };
+
+    text.split(separator)
+        .flat_map(|chunk| paragraphs(chunk, settings))
+        .map(|(_, lines)| join_lines(&lines, settings))
+        .collect::<Vec<_>>()
+        .join(settings.newline)
+}
@@ -0,0 +1,269 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], input: &str) -> Output {
+    run_with_columns(args, input, None)
+}
+
+/// Runs the binary with `$COLUMNS` set to `columns`, or unset. The standard streams are never
+/// terminals.
+fn run_with_columns(args: &[&str], input: &str, columns: Option<&str>) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_justify"));
+    // Keep the user's configuration files out of the way.
+    command.env("XDG_CONFIG_HOME", "/nonexistent").current_dir(std::env::temp_dir());
+    run_command(command, args, input, columns)
+}
+
+fn run_command(mut command: Command, args: &[&str], input: &str, columns: Option<&str>) -> Output {
+    match columns {
+        Some(c) => command.env("COLUMNS", c),
+        None => command.env_remove("COLUMNS")
+    };
+    let mut child = command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn combined_short_flags() {
+    let out = run(&["-jl", "--width", "10"], "aaa bb c dd eee ff\n");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "aaa  bb  c\ndd  eee ff\n");
+}
+
+#[test]
+fn long_options_with_values() {
+    let out = run(&["-HW8", "--hyphen=~", "--insert-at=right"], "supercalifragilistic word\n");
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "superca~\nlifragi~\nlistic\nword\n");
+}
+
+#[test]
+fn random_insertion() {
+    let paragraph = "aaa bb c dd eee ff gg hh ii jj kk ll mm\n";
+    let input = format!("{}\n{}", paragraph, paragraph);
+    let out = run(&["-W12", "--insert-at=random:3"], &input);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "aaa bb c  dd\neee ff gg hh\nii jj kk  ll\nmm\n\naaa bb  c dd\neee ff gg hh\nii jj  kk ll\nmm\n");
+    let out = run(&["-W12", "--insert-at=stateless-random:3"], &input);
+    let once = String::from_utf8(run(&["-W12", "--insert-at=stateless-random:3"], paragraph).stdout).unwrap();
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), format!("{}\n{}", once, once));
+}
+
+#[test]
+fn unknown_option() {
+    let out = run(&["--widht=10"], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().contains("unknown option `--widht=10`"));
+    let out = run(&["--insert-at=middle"], "");
+    assert_eq!(out.status.code(), Some(2));
+    let out = run(&["--direction=up"], "");
+    assert_eq!(out.status.code(), Some(2));
+    let out = run(&["--ignore-spaces"], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().contains("ignore_spaces requires hyphenate_overflow"));
+}
+
+fn scratch_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("justify-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn files_and_output() {
+    let dir = scratch_dir("output");
+    let (a, b, out) = (dir.join("a.txt"), dir.join("b.txt"), dir.join("out.txt"));
+    std::fs::write(&a, "aaa bb c dd eee ff\n").unwrap();
+    std::fs::write(&b, "gg hh\n").unwrap();
+    let out_s = out.to_str().unwrap();
+    let res = run(&["10", a.to_str().unwrap(), "-", b.to_str().unwrap(), "-o", out_s], "ii jj\n");
+    assert!(res.status.success());
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "aaa  bb  c\ndd eee ff\n\nii jj\n\ngg hh\n");
+
+    let res = run(&[dir.join("missing").to_str().unwrap()], "");
+    assert_eq!(res.status.code(), Some(1));
+    assert!(String::from_utf8(res.stderr).unwrap().contains("missing"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn in_place() {
+    let dir = scratch_dir("in-place");
+    let a = dir.join("a.txt");
+    std::fs::write(&a, "aaa bb c dd eee ff\n").unwrap();
+    let res = run(&["--width=10", "--in-place=.orig", a.to_str().unwrap()], "");
+    assert!(res.status.success());
+    assert_eq!(std::fs::read_to_string(&a).unwrap(), "aaa  bb  c\ndd eee ff\n");
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt.orig")).unwrap(), "aaa bb c dd eee ff\n");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+
+    assert_eq!(run(&["--in-place"], "").status.code(), Some(2));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn paragraphs_are_reflowed() {
+    let input = "The first is erroneous,\nwhile the\nsecond is intentional.\n\n\n  Wrapping arithmetic\nis\n  fine.\n";
+    let out = run(&["--width=30"], input);
+    let justified = "The  first is erroneous, while\nthe second is intentional.\n\nWrapping arithmetic is fine.\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), justified);
+    let out = run(&["--width=30", "--separator=\\n--\\n"], input);
+    let justified = "The  first is erroneous, while\nthe second is intentional.\n--\nWrapping arithmetic is fine.\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), justified);
+}
+
+#[test]
+fn columns() {
+    let input = "The first is erroneous,\nwhile the second is intentional.\n\nWrapping arithmetic is fine.\n";
+    let out = run(&["-W", "41", "--columns=2", "--gutter=1"], input);
+    let justified = "The     first     is Wrapping  arithmetic\nerroneous, while the is fine.\nsecond            is\nintentional.\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), justified);
+    let out = run(&["-c", "-C2"], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn pages() {
+    let input = "The first is erroneous,\nwhile the second is intentional.\n\nWrapping arithmetic is fine.\n";
+    let out = run(&["-W", "20", "--page-length=4", "--header={title} {page}", "-t", "Report", "--orphans=1"], input);
+    let paginated = "      Report 1\nThe     first     is\nerroneous, while the\n\n      Report 2\nsecond            is\nintentional.\n\n      Report 3\nWrapping  arithmetic\nis fine.\n\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), paginated);
+    let out = run(&["-W", "20", "--page-length=4", "--header={title} {page}", "-t", "Q1\\\\Q2"], input);
+    assert!(String::from_utf8(out.stdout).unwrap().starts_with("      Q1\\Q2 1\n"));
+}
+
+#[test]
+fn boxed() {
+    let input = "The first is erroneous,\nwhile the second is intentional.\n";
+    let out = run(&["-W", "20", "--box=double", "--padding=0"], input);
+    let boxed = "╔══════════════════╗\n║The    first    is║\n║erroneous,   while║\n║the    second   is║\n║intentional.      ║\n╚══════════════════╝\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), boxed);
+    let out = run(&["--box=dotted"], "");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn terminal_width() {
+    let input = "aaa bb c dd eee ff gg hh\n";
+    let out = run_with_columns(&[], input, Some("12"));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "aaa  bb c dd\neee ff gg hh\n");
+    let out = run_with_columns(&["--width", "-4"], input, Some("12"));
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "aaa bb c\ndd   eee\nff gg hh\n");
+    let out = run_with_columns(&["-W10", "--width=auto"], input, None);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "aaa bb c dd eee ff gg hh\n");
+    // Output that doesn't go to standard output is justified to 80 columns, whatever the terminal.
+    let long = "The first is erroneous, while the second is intentional. The first is erroneous, while the second is intentional.\n";
+    let justified = "The first is erroneous, while the second is intentional. The first is erroneous,\nwhile the second is intentional.\n";
+    assert_eq!(run_with_columns(&["--check"], justified, Some("30")).status.code(), Some(0));
+    let dir = scratch_dir("auto-width");
+    let file = dir.join("out.txt");
+    run_with_columns(&["-o", file.to_str().unwrap()], long, Some("30"));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), justified);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check() {
+    let justified = "The  first is erroneous, while\nthe second is intentional.\n";
+    let out = run(&["--width=30", "--check"], justified);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+    let out = run(&["--width=30", "-c"], "The first is erroneous, while\nthe second is intentional.\n");
+    assert_eq!(out.status.code(), Some(1));
+    let report = "<stdin>:1: expected `The  first is erroneous, while`, found `The first is erroneous, while`\n";
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), report);
+}
+
+#[test]
+fn config_files_and_profiles() {
+    let dir = scratch_dir("config");
+    let project = dir.join("project").join("src");
+    std::fs::create_dir_all(dir.join("xdg").join("justify")).unwrap();
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(dir.join("xdg/justify/config.toml"), "width = 8 # global\n[profile.wide]\nwidth = 20\njustify-last-line = true\n").unwrap();
+    std::fs::write(dir.join("project/.justify.toml"), "insert-at = 'right'\n\n[profile.wide]\nhyphen = \"~\"\n").unwrap();
+    let run_in = |args: &[&str]| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_justify"));
+        command.env("XDG_CONFIG_HOME", dir.join("xdg")).current_dir(&project);
+        String::from_utf8(run_command(command, args, "aaa bb c dd eee ff gg hhhhhhhhhhhhhhhhhhhhh\n", None).stdout).unwrap()
+    };
+    assert_eq!(run_in(&[]), "aaa bb c\ndd   eee\nff    gg\nhhhhhhhhhhhhhhhhhhhhh\n");
+    assert_eq!(run_in(&["-W10"]), "aaa  bb  c\ndd eee  ff\ngg\nhhhhhhhhhhhhhhhhhhhhh\n");
+    assert_eq!(run_in(&["--profile=wide", "-H"]), "aaa bb c dd  eee  ff\ngg\nhhhhhhhhhhhhhhhhhhh~\nhh\n");
+    assert_eq!(run_in(&["--no-config", "-W10"]), "aaa  bb  c\ndd  eee ff\ngg\nhhhhhhhhhhhhhhhhhhhhh\n");
+    let out = run(&["--profile=nope"], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().contains("unknown profile `nope`"));
+    std::fs::write(dir.join("project/words.txt"), "aaa\nbb\n").unwrap();
+    std::fs::write(dir.join("project/.justify.toml"), "[profile.boxed]\nbox = 'ascii'\ndictionary = 'words.txt'\n").unwrap();
+    assert!(run_in(&["-pboxed", "-W12"]).starts_with("+----------+\n| aaa bb c |\n| dd   eee |\n"));
+    std::fs::write(dir.join("project/.justify.toml"), "output = 'out.txt'\n").unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_justify"));
+    command.env("XDG_CONFIG_HOME", "/nonexistent").current_dir(&project);
+    let out = run_command(command, &[], "", None);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().contains("unknown key `output`"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dictionary_file() {
+    let dir = scratch_dir("dictionary");
+    let list = dir.join("words.txt");
+    std::fs::write(&list, "# Thai\nสวัสดี\nครับ\n").unwrap();
+    let out = run(&["-W15", "-d", list.to_str().unwrap()], "สวัสดีครับ\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "สวัสดี\nครับ\n");
+    let out = run(&["--dictionary", dir.join("missing.txt").to_str().unwrap()], "");
+    assert_eq!(out.status.code(), Some(2));
+    assert!(String::from_utf8(out.stderr).unwrap().contains("can't read dictionary"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn ambiguous_wide() {
+    let out = run(&["-wA", "-W12"], "a·b 20° c—d e f\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "a·b    20°\nc—d e f\n");
+}
+
+#[cfg(feature="normalization")]
+#[test]
+fn normalize() {
+    let input = "Cafe\u{301} re\u{301}sume\u{301} de\u{301}ja\u{300} vu\n";
+    let out = run(&["-W15", "--normalize=nfc"], input);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "Caf\u{e9}     r\u{e9}sum\u{e9}\nd\u{e9}j\u{e0} vu\n");
+    let out = run(&["-W15", "--normalize=nfc", "--preserve-form"], input);
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "Cafe\u{301}     re\u{301}sume\u{301}\nde\u{301}ja\u{300} vu\n");
+    assert_eq!(run(&["--normalize=nfd"], "").status.code(), Some(2));
+}
+
+#[test]
+fn language() {
+    let out = run(&["-W20", "--language=cs_CZ.UTF-8"], "Byl jsem tam a v domě s kočkou a psem.\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "Byl     jsem     tam\na  v domě s kočkou\na psem.\n");
+    let out = run(&["-W10", "-L", "fr"], "Quoi? Non!\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "Quoi\u{202F}?\nNon\u{202F}!\n");
+    assert_eq!(run(&["--language=english"], "").status.code(), Some(2));
+}
+
+#[test]
+fn hyphenation_options() {
+    let dir = scratch_dir("exceptions");
+    let list = dir.join("exceptions.txt");
+    std::fs::write(&list, "ta-ble\n").unwrap();
+    let out = run(&["-HW4", "--right-hyphen-min=2", "-e", list.to_str().unwrap()], "table words\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "ta-\nble\nwor-\nds\n");
+    let out = run(&["-HW4", "--hyphen-limit=1"], "wordsmith\n");
+    assert_eq!(String::from_utf8(out.stdout).unwrap(), "wor-\ndsmith\n");
+    assert_eq!(run(&["-HW4", "--left-hyphen-min=4"], "").status.code(), Some(2));
+    let out = run(&["--exceptions", dir.join("missing.txt").to_str().unwrap()], "");
+    assert_eq!(out.status.code(), Some(2));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
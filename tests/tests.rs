@@ -1,5 +1,11 @@
 extern crate justify;
-use justify::{Settings, justify, justify_paragraph, InsertAt};
+#[cfg(feature="serde")] extern crate serde_json;
+#[cfg(feature="serde")] extern crate toml;
+use justify::{Settings, justify, justify_paragraph, InsertAt, SingleWord};
+use justify::{Line, SpaceDistributor, SeededRandom, River, find_rivers, Mismatch, check_justified, unjustify};
+use justify::{InsertAtBuf, SettingsBuf, SettingsError, justify_columns, Columns, Pagination, paginate};
+use justify::{BoxStyle, Frame, justify_boxed, HyphenationExceptions};
+use std::sync::Mutex;
 #[test]
 fn less_than_width() {
     let settings = Settings::default();
@@ -64,6 +70,31 @@ fn multi_hyphenate() {
     assert_eq!(justify(plain, &settings), justified);
 }
 
+#[test]
+fn hyphenation_limits() {
+    let settings = Settings { width: 4, hyphenate_overflow: true, right_hyphen_min: 2, ..Settings::default() };
+    let plain = "Supercalifragilisticexpialidocious";
+    let justified = "Sup-\nerc-\nali-\nfra-\ngil-\nist-\nice-\nxpi-\nali-\ndoc-\nio-\nus";
+    assert_eq!(justify(plain, &settings), justified);
+    let settings = Settings { width: 5, left_hyphen_min: 4, ..settings.clone() };
+    assert_eq!(justify("Thermodynamics", &settings), "Ther-\nmody-\nnami-\ncs");
+    assert_eq!(Settings { width: 4, ..settings }.validate(), Err(SettingsError::HyphenMinTooWide));
+    let settings = Settings { width: 4, hyphenate_overflow: true, max_hyphenated_lines: 3, ..Settings::default() };
+    assert_eq!(justify(plain, &settings), "Sup-\nerc-\nali-\nfragilisticexpialidocious");
+}
+
+#[test]
+fn hyphenation_exceptions() {
+    let exceptions = HyphenationExceptions::from_list("# comment\nta-ble\nSuper-cali-fragilistic-expiali-docious\nlinux\n");
+    assert_eq!(exceptions.breaks("TABLE"), Some(&[2][..]));
+    assert_eq!(exceptions.breaks("chair"), None);
+    let settings = Settings { width: 8, hyphenate_overflow: true, hyphenation_exceptions: Some(&exceptions), ..Settings::default() };
+    let justified = "Super-\ncali-\nfragilistic-\nexpiali-\ndocious,\ntables";
+    assert_eq!(justify("Supercalifragilisticexpialidocious, tables", &settings), justified);
+    let settings = Settings { width: 4, ..settings.clone() };
+    assert_eq!(justify("table linux", &settings), "ta-\nble\nlinux");
+}
+
 #[test]
 #[should_panic(expected = "Expected `text` to contain no newlines but it did")]
 fn justify_paragraph_with_newline() {
@@ -108,6 +139,14 @@ fn many_empty_lines_justify() {
     assert_eq!(justify("\n\n\n\n\n\n\nQuestions are  good\n\n\n\n\n\n", &settings), "Questions\nare good");
 }
 
+#[cfg(feature="unicode-width")]
+#[test]
+fn wide_word_after_break() {
+    // A line broken before a wide word starts as wide as the word is in columns, not bytes.
+    let settings = Settings { width: 8, wcwidth: true, ..Settings::default() };
+    assert_eq!(justify("aaaaaa 漢字漢 b c d e f", &settings), "aaaaaa\n漢字漢 b\nc d e f");
+}
+
 #[cfg(feature="unicode-width")]
 #[test]
 fn test_unicode_cjk_justify() {
@@ -115,17 +154,16 @@ fn test_unicode_cjk_justify() {
     let plain = "Lorem ipsum dolor 新しく作成した sit amet, consectetur adipiscing elit. Vivamus 機能をテストするために viverra tempor dolor vitae tempus. CJK （中国語、日本語または韓国語） Duis imperdiet faucibus magna sed convallis. In ullamcorper a quam eu blandit. Aenean sagittis sit amet risus at condimentum. テ Integer キ venenatis ス a turpis a porttitor. ト Aliquam eu justo nec metus が無作為に必要ですが、 egestas suscipit eu sed libero. Mauris a ultrices tortor. 日本語 Vestibulum わかりません。 ante ipsum primis in faucibus orci luctus et ultrices posuere cubilia Curae; Nunc fringilla tempor pellentesque. Pellentesque facilisis mi eu condimentum interdum. Orci varius natoque penatibus et magnis dis parturient montes, nascetur ridiculus mus. Mauris consequat luctus condimentum. Sed eget purus elit. Curabitur pretium elementum enim, gravida sodales neque mollis ut. Curabitur malesuada euismod fermentum.";
     let justified = "Lorem  ipsum dolor 新しく作成した sit amet, consectetur adipiscing elit. Vivamus
 機能をテストするために     viverra    tempor    dolor    vitae    tempus.    CJK
-（中国語、日本語または韓国語）     Duis    imperdiet    faucibus    magna    sed
-convallis.  In ullamcorper a quam eu blandit. Aenean sagittis sit amet risus  at
-condimentum.  テ  Integer  キ venenatis ス a turpis a porttitor. ト  Aliquam  eu
-justo  nec metus が無作為に必要ですが、 egestas suscipit eu sed libero. Mauris a
-ultrices  tortor. 日本語 Vestibulum わかりません。 ante ipsum primis in faucibus
-orci   luctus   et  ultrices  posuere  cubilia  Curae;  Nunc  fringilla   tempor
-pellentesque.  Pellentesque  facilisis mi eu condimentum interdum.  Orci  varius
-natoque  penatibus  et  magnis dis parturient montes,  nascetur  ridiculus  mus.
-Mauris  consequat  luctus  condimentum. Sed eget purus elit.  Curabitur  pretium
-elementum  enim,  gravida sodales neque mollis ut. Curabitur  malesuada  euismod
-fermentum.";
+（中国語、日本語または韓国語）  Duis imperdiet faucibus magna sed convallis.  In
+ullamcorper a quam eu blandit. Aenean sagittis sit amet risus at condimentum. テ
+Integer  キ  venenatis  ス a turpis a porttitor. ト Aliquam eu justo  nec  metus
+が無作為に必要ですが、 egestas suscipit eu sed libero. Mauris a ultrices tortor.
+日本語  Vestibulum  わかりません。 ante ipsum primis in faucibus orci luctus  et
+ultrices posuere cubilia Curae; Nunc fringilla tempor pellentesque. Pellentesque
+facilisis  mi  eu condimentum interdum. Orci varius natoque penatibus et  magnis
+dis   parturient  montes,  nascetur  ridiculus  mus.  Mauris  consequat   luctus
+condimentum.  Sed  eget  purus elit. Curabitur pretium elementum  enim,  gravida
+sodales neque mollis ut. Curabitur malesuada euismod fermentum.";
     assert_eq!(justify(plain, &settings), justified);
 }
 
@@ -139,6 +177,30 @@ fn test_unicode_cjk_only() {
     assert_eq!(res, justified);
 }
 
+#[cfg(feature="unicode-width")]
+#[test]
+fn ambiguous_wide() {
+    let narrow = Settings { width: 12, wcwidth: true, ..Settings::default() };
+    let wide = Settings { ambiguous_wide: true, ..narrow.clone() };
+    let plain = "a·b 20° c—d e f";
+    assert_eq!(justify(plain, &narrow), "a·b  20° c—d\ne f");
+    assert_eq!(justify(plain, &wide), "a·b    20°\nc—d e f");
+    let settings = Settings { hyphenate_overflow: true, hyphen: "", ..wide.clone() };
+    assert_eq!(justify("──────────────", &settings), "──────\n──────\n──");
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn emoji_sequences() {
+    let settings = Settings { width: 12, wcwidth: true, ..Settings::default() };
+    let plain = "👩‍👩‍👧 🇯🇵 ❤️ 👍🏽 ok 1️⃣ 🏳️‍🌈";
+    assert_eq!(justify(plain, &settings), "👩‍👩‍👧  🇯🇵 ❤️ 👍🏽\nok 1️⃣ 🏳️‍🌈");
+    let settings = Settings { width: 10, single_word: SingleWord::LetterSpacing, ..settings.clone() };
+    assert_eq!(justify("👍🏽🇯🇵👩‍👩‍👧 goodnight", &settings), "👍🏽  🇯🇵  👩‍👩‍👧\ngoodnight");
+    let settings = Settings { width: 5, hyphenate_overflow: true, hyphen: "-", ..settings.clone() };
+    assert_eq!(justify("👍🏽🇯🇵👩‍👩‍👧❤️", &settings), "👍🏽🇯🇵-\n👩‍👩‍👧❤️");
+}
+
 #[cfg(feature="unicode-width")]
 #[test]
 /// This test is from https://ja.wikipedia.org/wiki/Rust_(%E3%83%97%E3%83%AD%E3%82%B0%E3%83%A9%E3%83%9F%E3%83%B3%E3%82%B0%E8%A8%80%E8%AA%9E)
@@ -202,3 +264,426 @@ fn thai_justify() {
     let res = justify(plain, &settings);
     assert_eq!(res, justified);
 }
+
+#[test]
+fn single_word_letter_spacing() {
+    let settings = Settings { width: 12, single_word: SingleWord::LetterSpacing, ..Settings::default() };
+    let plain = "Reformatting Justified text";
+    let justified = "Reformatting\nJ u stifie d\ntext";
+    assert_eq!(justify(plain, &settings), justified);
+    // Decomposed accents stay on their letters.
+    let plain = "Re\u{301}sume\u{301}s de\u{301}ja\u{300} vus";
+    assert_eq!(justify(plain, &settings), "R e\u{301}sume\u{301}s\nde\u{301}ja\u{300} vus");
+}
+
+#[test]
+fn single_word_fill() {
+    let settings = Settings { width: 10, justify_last_line: true, single_word: SingleWord::Fill('.'), ..Settings::default() };
+    assert_eq!(justify("Heading\nChapter one", &settings), "Heading...\n\n\nChapter...\none.......\n");
+}
+
+#[test]
+fn seeded_random_distributor() {
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error. In the following discussion, we maintain a distinction between arithmetic overflow and wrapping arithmetic. The first is erroneous, while the second is intentional.";
+    let justified = "If a program contains arithmetic overflow, the  programmer has made an error. In
+the following discussion, we maintain a  distinction between arithmetic overflow
+and  wrapping  arithmetic.   The  first  is  erroneous,  while  the   second  is
+intentional.";
+    let random = Mutex::new(SeededRandom::new(1));
+    let settings = Settings { insert_at: InsertAt::Distributor(&random), ..Settings::default() };
+    let res = justify(plain, &settings);
+    assert_eq!(res, justified);
+    *random.lock().unwrap() = SeededRandom::new(1);
+    let on_thread = || justify(plain, &Settings { insert_at: InsertAt::Distributor(&random), ..Settings::default() });
+    assert_eq!(std::thread::scope(|s| s.spawn(on_thread).join().unwrap()), res);
+}
+
+struct Broken;
+
+impl SpaceDistributor for Broken {
+    fn distribute(&mut self, line: &Line) -> Vec<usize> {
+        vec![line.spaces; line.gaps()]
+    }
+}
+
+#[test]
+fn invalid_distribution_falls_back_to_balanced() {
+    let plain = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Vivamus viverra tempor dolor vitae tempus.";
+    let broken = Mutex::new(Broken);
+    let settings = Settings { insert_at: InsertAt::Distributor(&broken), ..Settings::default() };
+    assert_eq!(justify(plain, &settings), justify(plain, &Settings::default()));
+    let out_of_range = |_, _, gaps, _: &Vec<&str>| gaps;
+    let settings = Settings { insert_at: InsertAt::Custom(&out_of_range), ..Settings::default() };
+    assert_eq!(justify(plain, &settings), justify(plain, &Settings::default()));
+}
+
+#[test]
+fn custom_with_state() {
+    let calls = std::cell::Cell::new(0);
+    let counting = |j, _, gaps, _: &Vec<&str>| { calls.set(calls.get() + 1); j % gaps };
+    let settings = Settings { width: 12, insert_at: InsertAt::Custom(&counting), ..Settings::default() };
+    assert_eq!(justify("aaa bb c dd eee", &settings), "aaa  bb c dd\neee");
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn justify_alternating() {
+    let settings = Settings { width: 30, insert_at: InsertAt::Alternating, ..Settings::default() };
+    let plain = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Vivamus viverra tempor dolor vitae tempus. Duis imperdiet faucibus magna sed convallis.";
+    let justified = "Lorem  ipsum  dolor  sit amet,
+consectetur  adipiscing  elit.
+Vivamus  viverra  tempor dolor
+vitae tempus.  Duis  imperdiet
+faucibus magna sed convallis.";
+    assert_eq!(justify(plain, &settings), justified);
+}
+
+#[derive(Default)]
+struct Recorder {
+    seen: Vec<(usize, usize, Option<Vec<usize>>)>
+}
+
+impl SpaceDistributor for Recorder {
+    fn distribute(&mut self, line: &Line) -> Vec<usize> {
+        self.seen.push((line.paragraph, line.number, line.previous.map(<[usize]>::to_vec)));
+        let mut add_v = vec![0; line.gaps()];
+        add_v[0] = line.spaces;
+        add_v
+    }
+}
+
+#[test]
+fn distributor_line_context() {
+    let recorder = Mutex::new(Recorder::default());
+    let settings = Settings { width: 12, insert_at: InsertAt::Distributor(&recorder), ..Settings::default() };
+    let justified = "aaa  bb c dd\neee   f g hh\nii jjj\n\nfff gg hh ii\njj";
+    assert_eq!(justify("aaa bb c dd eee f g hh ii jjj\nfff gg hh ii jj", &settings), justified);
+    let seen = recorder.into_inner().unwrap().seen;
+    assert_eq!(seen, vec![(0, 0, None), (0, 1, Some(vec![1, 0, 0])), (1, 0, None)]);
+}
+
+#[test]
+fn rivers_found() {
+    let settings = Settings::default();
+    let text = "  aa bb  c\nddd eee  f\n\ng  h  ii\njj k lll";
+    let rivers = vec![
+        River { column: 7, line: 0, length: 2 },
+        River { column: 8, line: 0, length: 2 },
+        River { column: 2, line: 3, length: 2 },
+        River { column: 4, line: 3, length: 2 },
+    ];
+    assert_eq!(find_rivers(text, &settings, 2), rivers);
+    assert_eq!(find_rivers(text, &settings, 3), vec![]);
+}
+
+#[test]
+fn justify_avoiding_rivers() {
+    let plain = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Vivamus viverra tempor dolor vitae tempus. Duis imperdiet faucibus magna sed convallis. In ullamcorper a quam eu blandit. Aenean sagittis sit amet risus at condimentum. Integer venenatis a turpis a porttitor. Aliquam eu justo nec metus egestas suscipit eu sed libero. Mauris a ultrices tortor. Vestibulum ante ipsum primis in faucibus orci luctus et ultrices posuere cubilia Curae; Nunc fringilla tempor pellentesque.";
+    let justified = "Lorem  ipsum dolor sit amet, consectetur
+adipiscing  elit. Vivamus viverra tempor
+dolor   vitae  tempus.  Duis   imperdiet
+faucibus   magna   sed   convallis.   In
+ullamcorper  a  quam eu blandit.  Aenean
+sagittis  sit amet risus at condimentum.
+Integer venenatis a turpis a  porttitor.
+Aliquam   eu  justo  nec  metus  egestas
+suscipit   eu  sed  libero.   Mauris   a
+ultrices tortor.  Vestibulum ante  ipsum
+primis  in   faucibus  orci  luctus   et
+ultrices  posuere  cubilia  Curae;  Nunc
+fringilla tempor pellentesque.";
+    let settings = Settings { width: 40, insert_at: InsertAt::AvoidRivers, ..Settings::default() };
+    let res = justify(plain, &settings);
+    assert_eq!(res, justified);
+    let length = |text: &str| find_rivers(text, &settings, 3).iter().map(|r| r.length).sum::<usize>();
+    let left = justify(plain, &Settings { width: 40, insert_at: InsertAt::Left, ..Settings::default() });
+    assert!(length(&res) < length(&left));
+}
+
+#[test]
+fn no_widow() {
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error. In the following discussion, we maintain a distinction between arithmetic overflow and wrapping arithmetic. The first is erroneous, while the second is intentional.";
+    let settings = Settings { min_last_line_words: 2, ..Settings::default() };
+    let justified = "If  a  program contains arithmetic overflow, the programmer has made  an  error.
+In  the  following  discussion,  we maintain a  distinction  between  arithmetic
+overflow  and  wrapping  arithmetic. The first is erroneous,  while  the  second
+is intentional.";
+    assert_eq!(justify(plain, &settings), justified);
+    // A word that is alone on its line anyway doesn't stop the paragraph being broken again.
+    let settings = Settings { width: 20, min_last_line_words: 2, ..Settings::default() };
+    assert_eq!(justify("extraordinarilylongwordhere aaa bbb ccc ddd eee fff ggg hhh iii jjj kkk", &settings), "extraordinarilylongwordhere\naaa   bbb  ccc   ddd\neee   fff  ggg   hhh\niii jjj kkk");
+    let settings = Settings { min_last_line_width: 20, ..Settings::default() };
+    let justified = "If   a  program  contains  arithmetic  overflow,  the  programmer  has  made  an
+error.   In  the  following  discussion,  we  maintain  a  distinction   between
+arithmetic  overflow  and  wrapping arithmetic. The first  is  erroneous,  while
+the second is intentional.";
+    assert_eq!(justify(plain, &settings), justified);
+}
+
+#[test]
+fn check_justified_text() {
+    let settings = Settings { width: 30, ..Settings::default() };
+    let justified = "The  first is erroneous, while\nthe second is intentional.\n\nWrapping arithmetic is fine.\n";
+    assert_eq!(check_justified(justified, &settings), vec![]);
+    let unjustified = "The first is erroneous,\nwhile the second is intentional.\n\nWrapping arithmetic is fine.\n";
+    let mismatches = vec![
+        Mismatch { line: 1, expected: Some("The  first is erroneous, while".to_owned()), found: Some("The first is erroneous,".to_owned()) },
+        Mismatch { line: 2, expected: Some("the second is intentional.".to_owned()), found: Some("while the second is intentional.".to_owned()) },
+    ];
+    assert_eq!(check_justified(unjustified, &settings), mismatches);
+    let missing = "The  first is erroneous, while\n\nWrapping arithmetic is\nfine.";
+    let mismatches = vec![
+        Mismatch { line: 1, expected: Some("The first is erroneous, while".to_owned()), found: Some("The  first is erroneous, while".to_owned()) },
+        Mismatch { line: 3, expected: Some("Wrapping arithmetic is fine.".to_owned()), found: Some("Wrapping arithmetic is".to_owned()) },
+        Mismatch { line: 4, expected: None, found: Some("fine.".to_owned()) },
+    ];
+    assert_eq!(check_justified(missing, &settings), mismatches);
+}
+
+#[test]
+fn unjustify_round_trip() {
+    let settings = Settings { width: 30, ..Settings::default() };
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error.\nThe first is erroneous, while the second is intentional.";
+    let justified = justify(plain, &settings);
+    assert_eq!(unjustify(&justified, &settings), plain);
+    let wider = Settings { width: 50, ..Settings::default() };
+    assert_eq!(justify(&unjustify(&justified, &settings), &wider), justify(plain, &wider));
+}
+
+#[test]
+fn unjustify_hyphenated() {
+    let settings = Settings { width: 10, hyphenate_overflow: true, ..Settings::default() };
+    let justified = "123456 789\n1234567890\n123456789-\n012    123\n456 89";
+    assert_eq!(unjustify(justified, &settings), "123456 789 1234567890 123456789012 123 456 89");
+    // A hyphen that leaves room for more of the word was not added by `hyphenate_overflow`.
+    let settings = Settings { width: 5, hyphenate_overflow: true, ..Settings::default() };
+    assert_eq!(justify("pre- and post", &settings), "pre-\nand\npost");
+    assert_eq!(unjustify("pre-\nand\npost", &settings), "pre- and post");
+    // Words broken with hyphenation minimums, or at the places an exception gives, are joined too.
+    let plain = "Supercalifragilisticexpialidocious Thermodynamics";
+    let settings = Settings { width: 5, hyphenate_overflow: true, left_hyphen_min: 3, right_hyphen_min: 3, ..Settings::default() };
+    assert_eq!(unjustify(&justify(plain, &settings), &settings), plain);
+    let settings = Settings { width: 4, right_hyphen_min: 2, left_hyphen_min: 1, ..settings };
+    assert_eq!(unjustify(&justify(plain, &settings), &settings), plain);
+    let exceptions = HyphenationExceptions::from_list("Super-cali-fragilistic-expiali-docious");
+    let settings = Settings { width: 8, hyphenate_overflow: true, hyphenation_exceptions: Some(&exceptions), ..Settings::default() };
+    assert_eq!(unjustify(&justify(plain, &settings), &settings), plain);
+    assert_eq!(check_justified(&justify(plain, &settings), &settings), vec![]);
+}
+
+#[test]
+fn stateless_random() {
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error. In the following discussion, we maintain a distinction between arithmetic overflow and wrapping arithmetic.\n\nThe first is erroneous, while the second is intentional.";
+    let settings = Settings { width: 40, insert_at: InsertAt::StatelessRandom(7), ..Settings::default() };
+    let res = justify(plain, &settings);
+    assert_eq!(justify(plain, &settings), res);
+    let on_thread = || justify(plain, &Settings { width: 40, insert_at: InsertAt::StatelessRandom(7), ..Settings::default() });
+    assert_eq!(std::thread::scope(|s| s.spawn(on_thread).join().unwrap()), res);
+    assert_eq!(check_justified(&res, &settings), vec![]);
+    assert_ne!(justify(plain, &Settings { insert_at: InsertAt::StatelessRandom(8), ..settings }), res);
+}
+
+#[test]
+fn owned_settings() {
+    let buf = SettingsBuf { width: 30, hyphen: "~".to_owned(), hyphenate_overflow: true, insert_at: InsertAtBuf::Right, ..SettingsBuf::default() };
+    let borrowed = Settings { width: 30, hyphen: "~", hyphenate_overflow: true, insert_at: InsertAt::Right, ..Settings::default() };
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error.";
+    assert_eq!(justify(plain, &buf.settings()), justify(plain, &borrowed));
+    let handle = std::thread::spawn(move || justify(plain, &Settings::from(&buf)));
+    assert_eq!(handle.join().unwrap(), justify(plain, &borrowed));
+}
+
+#[cfg(feature="serde")]
+#[test]
+fn serialized_settings() {
+    let buf = SettingsBuf { width: 72, insert_at: InsertAtBuf::StatelessRandom(3), single_word: SingleWord::Fill('-'), ..SettingsBuf::default() };
+    let json = serde_json::to_string(&buf).unwrap();
+    assert_eq!(serde_json::from_str::<SettingsBuf>(&json).unwrap(), buf);
+    assert_eq!(toml::from_str::<SettingsBuf>(&toml::to_string(&buf).unwrap()).unwrap(), buf);
+
+    let partial: SettingsBuf = serde_json::from_str(r#"{"width": 40, "insert_at": "avoid_rivers"}"#).unwrap();
+    assert_eq!(partial, SettingsBuf { width: 40, insert_at: InsertAtBuf::AvoidRivers, ..SettingsBuf::default() });
+    let partial: SettingsBuf = toml::from_str("hyphen = \"=\"\ninsert_at = { stateless_random = 5 }").unwrap();
+    assert_eq!(partial, SettingsBuf { hyphen: "=".to_owned(), insert_at: InsertAtBuf::StatelessRandom(5), ..SettingsBuf::default() });
+}
+
+#[test]
+fn builder() {
+    let settings = Settings::builder().width(10).hyphenate_overflow(true).hyphen("~").wcwidth(true).build().unwrap();
+    assert_eq!(justify("123456789012 3", &settings), "123456789~\n012 3");
+    assert_eq!(Settings::builder().width(0).build().err(), Some(SettingsError::ZeroWidth));
+    assert_eq!(Settings::builder().width(2).hyphen("--").hyphenate_overflow(true).build().err(), Some(SettingsError::HyphenTooWide));
+    assert!(Settings::builder().width(2).hyphen("--").build().is_ok());
+    assert_eq!(Settings::builder().ignore_spaces(true).build().err(), Some(SettingsError::IgnoreSpacesWithoutHyphenation));
+    assert_eq!(SettingsBuf { width: 0, ..SettingsBuf::default() }.settings().validate(), Err(SettingsError::ZeroWidth));
+}
+
+#[test]
+fn columns() {
+    let plain = "The first is erroneous, while the second is intentional.";
+    let settings = Settings { width: 43, ..Settings::default() };
+    let layout = Columns { count: 4, gutter: 1, balance: false };
+    assert_eq!(justify_columns(plain, &settings, &layout), "The  first erroneous, second  is\nis         while  the intentional.");
+    let layout = Columns { balance: true, ..layout };
+    assert_eq!(justify_columns(plain, &settings, &layout), "The  first erroneous, second  is intentional.\nis         while  the");
+}
+
+#[test]
+fn pages() {
+    let plain = "If a program contains arithmetic overflow, the programmer has made an error. In the following discussion, we maintain a distinction between arithmetic overflow and wrapping arithmetic.\nThe first is erroneous, while the second is intentional.";
+    let settings = Settings { width: 30, ..Settings::default() };
+    let justified = justify(plain, &settings);
+    let pagination = Pagination { length: 9, header: "{title}\n", footer: "{page}/{pages}", title: "Overflow", ..Pagination::default() };
+    assert_eq!(paginate(&justified, &settings, &pagination), "           Overflow\n\nIf    a    program    contains\narithmetic    overflow,    the\nprogrammer  has made an error.\nIn  the following  discussion,\nwe   maintain  a   distinction\n\n             1/2\n           Overflow\n\nbetween   arithmetic  overflow\nand wrapping arithmetic.\n\nThe  first is erroneous, while\nthe second is intentional.\n\n             2/2");
+    // Only one line of the first paragraph would be left for the second page.
+    let pagination = Pagination { length: 6, form_feed: true, ..Pagination::default() };
+    assert_eq!(paginate(&justified, &settings, &pagination), "If    a    program    contains\narithmetic    overflow,    the\nprogrammer  has made an error.\nIn  the following  discussion,\nwe   maintain  a   distinction\n\x0Cbetween   arithmetic  overflow\nand wrapping arithmetic.\n\nThe  first is erroneous, while\nthe second is intentional.");
+    let pagination = Pagination { widows: 1, ..pagination };
+    assert_eq!(paginate(&justified, &settings, &pagination), "If    a    program    contains\narithmetic    overflow,    the\nprogrammer  has made an error.\nIn  the following  discussion,\nwe   maintain  a   distinction\nbetween   arithmetic  overflow\n\x0Cand wrapping arithmetic.\n\nThe  first is erroneous, while\nthe second is intentional.");
+}
+
+#[test]
+fn boxed() {
+    let plain = "The first is erroneous, while the second is intentional.";
+    let settings = Settings { width: 24, ..Settings::default() };
+    let frame = Frame { title: "Overflow", ..Frame::default() };
+    assert_eq!(justify_boxed(plain, &settings, &frame), "\
+┌─ Overflow ───────────┐
+│ The     first     is │
+│ erroneous, while the │
+│ second            is │
+│ intentional.         │
+└──────────────────────┘");
+    let frame = Frame { style: BoxStyle::Ascii, padding: 0, title: "Arithmetic overflow" };
+    assert_eq!(justify_boxed(plain, &settings, &frame), "\
++- Arithmetic overflo -+
+|The      first      is|
+|erroneous,  while  the|
+|second is intentional.|
++----------------------+");
+    // Every line of the box is as many characters wide, however many bytes they take.
+    let frame = Frame { title: "Dépassement de capacité", ..Frame::default() };
+    let boxed = justify_boxed("Le résultat dépasse la capacité du type.", &settings, &frame);
+    assert!(boxed.lines().all(|l| l.chars().count() == 24), "{}", boxed);
+}
+
+#[cfg(all(feature="bidi", feature="unicode-width"))]
+#[test]
+fn right_to_left() {
+    use justify::Direction;
+    let plain = "שלום עולם זהו טקסט בעברית עם מילה English באמצע של המשפט הזה.\nThe first is erroneous.";
+    let settings = Settings { width: 24, wcwidth: true, direction: Direction::Auto, visual_order: true, ..Settings::default() };
+    assert_eq!(justify(plain, &settings), "\
+טסקט   והז   םלוע   םולש
+English  הלימ םע  תירבעב
+     .הזה טפשמה לש עצמאב
+
+The first is erroneous.");
+    let settings = Settings { visual_order: false, ..settings };
+    assert_eq!(justify(plain, &settings), "\
+שלום   עולם   זהו   טקסט
+בעברית  עם מילה  English
+באמצע של המשפט הזה.     
+
+The first is erroneous.");
+}
+
+#[test]
+fn kashida() {
+    // Without `wcwidth`, Arabic letters and kashidas are two columns wide and spaces one.
+    let plain = "بسم الله الرحمن الرحيم الحمد لله رب العالمين";
+    let settings = Settings { width: 30, kashida: true, ..Settings::default() };
+    assert_eq!(justify(plain, &settings), "\
+بسـم الله الرحمن
+الرحيم الحمد لله
+رب العالمين");
+    let plain = "The first is erroneous, while the second is intentional.";
+    assert_eq!(justify(plain, &settings), justify(plain, &Settings { width: 30, ..Settings::default() }));
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn kashida_ligatures() {
+    // Lam-Alef is a ligature, and the name of God is never elongated, so لا and the words with الله
+    // are left alone.
+    let plain = "الله سلام كلام لا بالله في الصلاة";
+    let settings = Settings { width: 27, kashida: true, wcwidth: true, ..Settings::default() };
+    assert_eq!(justify(plain, &settings), "الله  سـلام كـلام لا بالله فـي\nالصلاة");
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn dictionary_segmentation() {
+    use justify::Dictionary;
+    let plain = "สวัสดีครับ ผมชื่อสมชาย ผมเป็นครูสอนภาษา";
+    let dictionary = Dictionary::new(vec!["สวัสดี", "ครับ", "ผม", "ชื่อ", "เป็น", "ครู", "สอน", "ภาษา"]);
+    let settings = Settings { width: 16, dictionary: Some(&dictionary), wcwidth: true, ..Settings::default() };
+    assert_eq!(justify(plain, &settings), "สวัสดี  ครับ  ผม ชื่อ\nสมชาย ผมเป็นครูสอน\nภาษา");
+    let plain = "The first is erroneous.";
+    assert_eq!(justify(plain, &settings), justify(plain, &Settings { dictionary: None, ..settings }));
+
+    // Burmese, Lao and Khmer.
+    let dictionary = Dictionary::new(vec!["ສະບາຍດີ", "ຂອບໃຈ", "ភាសា", "ខ្មែរ", "မင်္ဂလာ", "ပါ"]);
+    let settings = Settings { width: 12, dictionary: Some(&dictionary), wcwidth: true, ..Settings::default() };
+    assert_eq!(justify("မင်္ဂလာပါ ສະບາຍດີຂອບໃຈ ភាសាខ្មែរ", &settings), "မင်္ဂလာ     ပါ\nສະບາຍດີ ຂອບໃຈ\nភាសាខ្មែរ");
+}
+
+#[cfg(all(feature="thai-dictionary", feature="unicode-width"))]
+#[test]
+fn thai_dictionary() {
+    use justify::Dictionary;
+    let dictionary = Dictionary::thai();
+    let settings = Settings { width: 20, dictionary: Some(&dictionary), wcwidth: true, ..Settings::default() };
+    let plain = "เป็นปฏิกิริยาที่เกิดขึ้นอย่างช้าๆ อาจจะกินเวลายาวนาน";
+    assert_eq!(justify(plain, &settings), "เป็น ปฏิกิริยาที่เกิดขึ้นอย่าง\nช้าๆ   อาจจะ กิน  เวลา\nยาวนาน");
+}
+
+#[cfg(feature="normalization")]
+#[test]
+fn normalization() {
+    use justify::Normalization;
+    // "e" followed by U+0301 COMBINING ACUTE ACCENT, as in NFD.
+    let plain = "Cafe\u{301} re\u{301}sume\u{301} de\u{301}ja\u{300} vu, nai\u{308}ve";
+    let nfc = Settings { width: 17, normalization: Normalization::Nfc, ..Settings::default() };
+    assert_eq!(justify(plain, &nfc), "Caf\u{e9}  r\u{e9}sum\u{e9} d\u{e9}j\u{e0}\nvu, na\u{ef}ve");
+    let preserved = Settings { preserve_form: true, ..nfc.clone() };
+    assert_eq!(justify(plain, &preserved), "Cafe\u{301}  re\u{301}sume\u{301} de\u{301}ja\u{300}\nvu, nai\u{308}ve");
+    let settings = Settings { width: 6, hyphenate_overflow: true, ..preserved.clone() };
+    assert_eq!(justify("re\u{301}sume\u{301}s", &settings), "re\u{301}sum-\ne\u{301}s");
+    let nfkc = Settings { width: 12, normalization: Normalization::Nfkc, ..Settings::default() };
+    assert_eq!(justify("the \u{FB01}nal \u{FF21}\u{FF22}\u{FF23}", &nfkc), "the    final\nABC");
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn no_break_spaces() {
+    let settings = Settings { width: 12, wcwidth: true, ..Settings::default() };
+    let justified = justify("see page\u{A0}12 of the book", &settings);
+    assert_eq!(justified, "see  page\u{A0}12\nof the book");
+    assert_eq!(check_justified(&justified, &settings), vec![]);
+    assert_eq!(unjustify(&justified, &settings), "see page\u{A0}12 of the book");
+    assert_eq!(justify("hello\u{3000}world\u{3000}again", &settings), "hello\u{3000}world\nagain");
+}
+
+#[cfg(feature="unicode-width")]
+#[test]
+fn language_rules() {
+    use justify::Language;
+    let french = Settings { width: 24, wcwidth: true, language: Language::French, ..Settings::default() };
+    let plain = "Il a dit : « Bonjour ! » et puis : « Vraiment? Oui; à 10:30. »";
+    let justified = justify(plain, &french);
+    assert_eq!(justified, "\
+Il a dit\u{202F}: «\u{202F}Bonjour\u{202F}!\u{202F}»
+et  puis\u{202F}:  «\u{202F}Vraiment\u{202F}?
+Oui\u{202F}; à 10:30.\u{202F}»");
+    assert_eq!(check_justified(&justified, &french), vec![]);
+    let unjustified = "Il a dit\u{202F}: «\u{202F}Bonjour\u{202F}!\u{202F}» et puis\u{202F}: «\u{202F}Vraiment\u{202F}? Oui\u{202F}; à 10:30.\u{202F}»";
+    assert_eq!(unjustify(&justified, &french), unjustified);
+    let czech = Settings { width: 20, wcwidth: true, language: Language::Czech, ..Settings::default() };
+    let plain = "Byl jsem tam a v domě s kočkou a psem.";
+    assert_eq!(justify(plain, &czech), "Byl     jsem     tam\na  v  domě s  kočkou\na psem.");
+    let german = Settings { width: 4, hyphenate_overflow: true, language: Language::German, ..Settings::default() };
+    assert_eq!(justify("Zuckerbrot", &german), "Zu-\ncke-\nrbr-\not");
+    let dutch = Settings { width: 12, single_word: SingleWord::LetterSpacing, language: Language::Dutch, ..Settings::default() };
+    assert_eq!(justify("IJsselmeer bijna", &dutch), "IJ sselmee r\nbijna");
+}